@@ -0,0 +1,53 @@
+// command line errors shared by the exercises, each one only raises the variants it needs
+
+use std::fmt;
+
+#[derive(PartialEq, Eq)]
+pub enum ArgError {
+    UnknownFlag(char),
+    MissingFormula,
+    TooManyArgs,
+    ConflictingRandom,
+    InvalidFormat(String),
+    InvalidTheme(String),
+    InvalidStyle(String),
+    InvalidSet(String),
+}
+
+impl fmt::Debug for ArgError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ArgError::UnknownFlag(c) => write!(f, "Unknown flag: '-{}'", c),
+            ArgError::MissingFormula => write!(f, "Missing formula"),
+            ArgError::TooManyArgs => write!(f, "Too many arguments"),
+            ArgError::ConflictingRandom => write!(f, "-r conflicts with a given formula"),
+            ArgError::InvalidFormat(s) => write!(
+                f,
+                "Invalid format: '{}', expected ascii, markdown, csv, json or latex",
+                s
+            ),
+            ArgError::InvalidTheme(s) => write!(
+                f,
+                "Invalid theme: '{}', expected none, classic or colorblind",
+                s
+            ),
+            ArgError::InvalidStyle(s) => {
+                write!(f, "Invalid style: '{}', expected ascii or unicode", s)
+            }
+            ArgError::InvalidSet(set) => write!(f, "Invalid set: '{}'", set),
+        }
+    }
+}
+
+// the -d and -r handling every exercise's parse_args must agree on
+#[cfg(test)]
+pub fn check_common_flags(parse: impl Fn(&[&str]) -> Option<ArgError>) {
+    assert_eq!(parse(&["AB&", "-d"]), None);
+    assert_eq!(parse(&["-r"]), None);
+    assert_eq!(parse(&["AB&", "-x"]), Some(ArgError::UnknownFlag('x')));
+    assert_eq!(parse(&["-dz"]), Some(ArgError::UnknownFlag('z')));
+    assert_eq!(parse(&[]), Some(ArgError::MissingFormula));
+    assert_eq!(parse(&["-d"]), Some(ArgError::MissingFormula));
+    assert_eq!(parse(&["AB&", "-r"]), Some(ArgError::ConflictingRandom));
+    assert_eq!(parse(&["-rr"]), Some(ArgError::ConflictingRandom));
+}
@@ -24,6 +24,56 @@ fn multiplier(a: u32, b: u32) -> u32 {
     result
 }
 
+// the same product, looping over the operand with the fewer set bits: one add per set bit
+#[allow(dead_code)]
+fn multiplier_fast(a: u32, b: u32) -> u32 {
+    if a.count_ones() < b.count_ones() {
+        multiplier(b, a)
+    } else {
+        multiplier(a, b)
+    }
+}
+
+// the full 64 bit product, kept as two words added with the 32 bit adder
+#[allow(dead_code)]
+fn multiplier_wide(a: u32, b: u32) -> u64 {
+    let (mut low, mut high) = (0u32, 0u32);
+    // a shifted left, spilling into the high word
+    let (mut multiplicand_low, mut multiplicand_high) = (a, 0u32);
+    let mut multiplier = b;
+
+    while multiplier != 0 {
+        if multiplier & 1 == 1 {
+            let sum = adder(low, multiplicand_low);
+            // the low word wrapped around, carry into the high one
+            let carry = (sum < low) as u32;
+            low = sum;
+            high = adder(adder(high, multiplicand_high), carry);
+        }
+        multiplier >>= 1;
+        multiplicand_high = (multiplicand_high << 1) | (multiplicand_low >> 31);
+        multiplicand_low <<= 1;
+    }
+    ((high as u64) << 32) | low as u64
+}
+
+// None when the product doesn't fit in 32 bits
+#[allow(dead_code)]
+fn checked_multiplier(a: u32, b: u32) -> Option<u32> {
+    let wide = multiplier_wide(a, b);
+    if wide >> 32 == 0 {
+        Some(wide as u32)
+    } else {
+        None
+    }
+}
+
+// clamps to u32::MAX when the product doesn't fit in 32 bits
+#[allow(dead_code)]
+fn saturating_multiplier(a: u32, b: u32) -> u32 {
+    checked_multiplier(a, b).unwrap_or(u32::MAX)
+}
+
 fn main() {
     let a = 6;
     let b = 7;
@@ -52,3 +102,51 @@ fn test_multiplier() {
     test(max, max);
     test(max, max - 1);
 }
+
+#[test]
+fn test_multiplier_wide() {
+    let max = u32::MAX;
+    let pairs = [
+        (0, 0),
+        (1, max),
+        (27, 15),
+        (2, max),
+        (max, max),
+        (max, max - 1),
+        (0x10000, 0x10000),
+        (0xffff, 0x10001),
+        (123456789, 987654321),
+    ];
+    for (a, b) in pairs {
+        assert_eq!(multiplier_wide(a, b), a as u64 * b as u64, "{} * {}", a, b);
+        assert_eq!(checked_multiplier(a, b), a.checked_mul(b), "{} * {}", a, b);
+        assert_eq!(
+            saturating_multiplier(a, b),
+            a.saturating_mul(b),
+            "{} * {}",
+            a,
+            b
+        );
+    }
+}
+
+#[test]
+fn test_multiplier_fast() {
+    let max = u32::MAX;
+    let pairs = [
+        (0, 0),
+        (0, max),
+        (max, 1),
+        (1, max),
+        (27, 15),
+        (0x8000_0000, 0x7fff_ffff),
+        (0x7fff_ffff, 0x8000_0000),
+        (0x0101_0101, 0xfedc_ba98),
+        (max, max),
+        (123456789, 987654321),
+    ];
+    for (a, b) in pairs {
+        assert_eq!(multiplier_fast(a, b), multiplier(a, b), "{} * {}", a, b);
+        assert_eq!(multiplier_fast(a, b), a.wrapping_mul(b), "{} * {}", a, b);
+    }
+}
@@ -2,6 +2,45 @@ fn gray_code(n: u32) -> u32 {
     n ^ (n >> 1)
 }
 
+// yields every assignment of n variables (first variable as the MSB) in gray code order,
+// with the position of the only variable that changed from the previous one
+// the first assignment has no previous one, so it reports n
+#[allow(dead_code)]
+fn gray_assignments(n: usize) -> impl Iterator<Item = (usize, Vec<bool>)> {
+    assert!(n < u32::BITS as usize, "too many variables");
+    (0..1u32 << n).map(move |k| {
+        let code = gray_code(k);
+        let changed = if k == 0 {
+            n
+        } else {
+            n - 1 - k.trailing_zeros() as usize
+        };
+        let assignment = (0..n).map(|j| (code >> (n - 1 - j)) & 1 == 1).collect();
+        (changed, assignment)
+    })
+}
+
+#[allow(dead_code)]
+fn reverse_bits(n: u32) -> u32 {
+    let mut result = 0;
+    let mut n = n;
+    for _ in 0..u32::BITS {
+        result = (result << 1) | (n & 1);
+        n >>= 1;
+    }
+    result
+}
+
+#[allow(dead_code, clippy::manual_rotate)]
+fn rotate_left(n: u32, k: u32) -> u32 {
+    // only the low 5 bits of k matter, like u32::rotate_left
+    let k = k & (u32::BITS - 1);
+    if k == 0 {
+        return n;
+    }
+    (n << k) | (n >> (u32::BITS - k))
+}
+
 fn main() {
     for n in 0..127 {
         let result = gray_code(n);
@@ -21,3 +60,54 @@ fn test_gray_code() {
     assert_eq!(gray_code(7), 4);
     assert_eq!(gray_code(8), 12);
 }
+
+#[test]
+fn test_reverse_bits() {
+    for n in [
+        0,
+        1,
+        2,
+        3,
+        0x8000_0000,
+        0xdead_beef,
+        0x1234_5678,
+        u32::MAX,
+        u32::MAX - 1,
+    ] {
+        assert_eq!(reverse_bits(n), n.reverse_bits(), "n = {:#x}", n);
+    }
+}
+
+#[test]
+fn test_rotate_left() {
+    for n in [0, 1, 0x8000_0000, 0xdead_beef, 0x1234_5678, u32::MAX] {
+        for k in [0, 1, 4, 16, 31, 32, 33, 100] {
+            assert_eq!(
+                rotate_left(n, k),
+                n.rotate_left(k),
+                "n = {:#x}, k = {}",
+                n,
+                k
+            );
+        }
+    }
+}
+
+#[test]
+fn test_gray_assignments() {
+    for n in 0..8 {
+        let all: Vec<(usize, Vec<bool>)> = gray_assignments(n).collect();
+        assert_eq!(all.len(), 1 << n);
+        assert_eq!(all[0], (n, vec![false; n]));
+        for pair in all.windows(2) {
+            let (_, prev) = &pair[0];
+            let (changed, next) = &pair[1];
+            let diff: Vec<usize> = (0..n).filter(|&j| prev[j] != next[j]).collect();
+            assert_eq!(diff, [*changed]);
+        }
+        let mut sorted: Vec<Vec<bool>> = all.into_iter().map(|(_, a)| a).collect();
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(sorted.len(), 1 << n);
+    }
+}
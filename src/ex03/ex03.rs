@@ -1,10 +1,14 @@
 // an AST to parse logical expressions in rpn
 
+#[allow(dead_code)]
+#[path = "../arg_error.rs"]
+mod arg_error;
 mod dot_graph;
 mod expr_generator;
 mod node;
 
 use crate::node::Node;
+use arg_error::ArgError;
 use dot_graph::create_graph;
 use expr_generator::random_rpn_expr;
 use node::ParseError;
@@ -19,43 +23,43 @@ struct Args {
     dot: bool,
 }
 
-fn parse_args() -> Result<Args, String> {
-    let mut args = args();
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Args, ArgError> {
     let mut expr = String::new();
     let mut dot = false;
-    let path = args.next().unwrap_or_else(|| "ex03".to_string());
     for arg in args {
         if let Some(arg) = arg.strip_prefix('-') {
             for c in arg.chars() {
                 match c {
                     'd' => dot = true,
                     'r' => {
-                        if expr.is_empty() {
-                            expr = random_rpn_expr();
-                        } else {
-                            return Err(path);
+                        if !expr.is_empty() {
+                            return Err(ArgError::ConflictingRandom);
                         }
+                        expr = random_rpn_expr();
                     }
-                    _ => return Err(path),
+                    _ => return Err(ArgError::UnknownFlag(c)),
                 }
             }
         } else if expr.is_empty() {
             expr = arg;
         } else {
-            return Err(path);
+            return Err(ArgError::TooManyArgs);
         }
     }
     if expr.is_empty() {
-        Err(path)
+        Err(ArgError::MissingFormula)
     } else {
         Ok(Args { expr, dot })
     }
 }
 
 fn main() -> Result<(), ParseError> {
-    let (expr, dot) = match parse_args() {
+    let mut args = args();
+    let path = args.next().unwrap_or_else(|| "ex03".to_string());
+    let (expr, dot) = match parse_args(args) {
         Ok(args) => (args.expr, args.dot),
-        Err(path) => {
+        Err(e) => {
+            println!("Error: {:?}", e);
             println!("Usage: {} <formula | -r> [-d]", path);
             println!("formula: a logical expression in rpn, ex: 101|&");
             println!("Options:");
@@ -75,6 +79,7 @@ fn main() -> Result<(), ParseError> {
 
 #[cfg(test)]
 mod tests {
+    use super::{arg_error, parse_args, ArgError};
     use crate::node::Node;
 
     #[allow(dead_code)]
@@ -138,4 +143,11 @@ mod tests {
         assert_eq!("1x^".parse::<Node>().err(), Some(InvalidCharacter('x')));
         assert_eq!("1x!".parse::<Node>().err(), Some(InvalidCharacter('x')));
     }
+
+    #[test]
+    fn ex03_parse_args() {
+        let parse = |args: &[&str]| parse_args(args.iter().map(|s| s.to_string())).err();
+        arg_error::check_common_flags(parse);
+        assert_eq!(parse(&["AB&", "BA&"]), Some(ArgError::TooManyArgs));
+    }
 }
@@ -1,62 +1,211 @@
 // an AST to parse logical expressions in rpn
 
+#[allow(dead_code)]
+#[path = "../arg_error.rs"]
+mod arg_error;
 mod dot_graph;
+mod export;
 mod expr_generator;
 mod node;
 
 use crate::node::Tree;
+use arg_error::ArgError;
 use dot_graph::create_graph;
 use expr_generator::random_rpn_expr;
 use node::ParseError;
 use std::env::args;
+use std::io::stdin;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Ascii,
+    Markdown,
+    Csv,
+    Json,
+    Latex,
+}
+
+impl TryFrom<&str> for Format {
+    type Error = ArgError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        match s {
+            "ascii" => Ok(Format::Ascii),
+            "markdown" => Ok(Format::Markdown),
+            "csv" => Ok(Format::Csv),
+            "json" => Ok(Format::Json),
+            "latex" => Ok(Format::Latex),
+            _ => Err(ArgError::InvalidFormat(s.to_string())),
+        }
+    }
+}
+
+// the ANSI SGR codes for the bits and the bar before the result, empty for no color
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct ColorTheme {
+    true_code: &'static str,
+    false_code: &'static str,
+    header_code: &'static str,
+}
+
+impl ColorTheme {
+    const NONE: ColorTheme = ColorTheme {
+        true_code: "",
+        false_code: "",
+        header_code: "",
+    };
+    // green and red
+    const CLASSIC: ColorTheme = ColorTheme {
+        true_code: "32",
+        false_code: "31",
+        header_code: "1;34",
+    };
+    // blue and orange, told apart with any kind of color blindness
+    const COLORBLIND: ColorTheme = ColorTheme {
+        true_code: "38;5;33",
+        false_code: "38;5;208",
+        header_code: "1",
+    };
+}
+
+impl Default for ColorTheme {
+    fn default() -> Self {
+        ColorTheme::NONE
+    }
+}
+
+impl TryFrom<&str> for ColorTheme {
+    type Error = ArgError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        match s {
+            "none" => Ok(ColorTheme::NONE),
+            "classic" => Ok(ColorTheme::CLASSIC),
+            "colorblind" => Ok(ColorTheme::COLORBLIND),
+            _ => Err(ArgError::InvalidTheme(s.to_string())),
+        }
+    }
+}
+
+// the characters the table is drawn with, the joints are on the line under the header
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct TableStyle {
+    vertical: char,
+    horizontal: char,
+    left_joint: char,
+    cross: char,
+    right_joint: char,
+}
+
+impl TableStyle {
+    const ASCII: TableStyle = TableStyle {
+        vertical: '|',
+        horizontal: '-',
+        left_joint: '|',
+        cross: '|',
+        right_joint: '|',
+    };
+    const UNICODE: TableStyle = TableStyle {
+        vertical: '│',
+        horizontal: '─',
+        left_joint: '├',
+        cross: '┼',
+        right_joint: '┤',
+    };
+}
+
+impl Default for TableStyle {
+    fn default() -> Self {
+        TableStyle::ASCII
+    }
+}
+
+impl TryFrom<&str> for TableStyle {
+    type Error = ArgError;
+
+    fn try_from(s: &str) -> Result<Self, Self::Error> {
+        match s {
+            "ascii" => Ok(TableStyle::ASCII),
+            "unicode" => Ok(TableStyle::UNICODE),
+            _ => Err(ArgError::InvalidStyle(s.to_string())),
+        }
+    }
+}
 
 struct Args {
     expr: String,
     dot: bool,
-    color: bool,
+    theme: ColorTheme,
+    style: TableStyle,
+    format: Format,
 }
 
 fn print_truth_table(formula: &str) {
-    match print_truth_table_color(formula, false) {
+    match print_truth_table_color(formula, &ColorTheme::NONE, &TableStyle::ASCII) {
         Ok(_) => (),
         Err(e) => eprintln!("{:?}", e),
     }
 }
 
-fn color_bit(bit: u32, color: bool) -> String {
-    if !color {
-        return format!("{}", bit);
+fn paint(s: &str, code: &str) -> String {
+    if code.is_empty() {
+        s.to_string()
+    } else {
+        format!("\x1b[{}m{}\x1b[0m", code, s)
     }
+}
+
+fn color_bit(bit: u32, theme: &ColorTheme) -> String {
     match bit {
-        0 => "\x1b[31m0\x1b[0m".to_string(),
-        1 => "\x1b[32m1\x1b[0m".to_string(),
+        0 => paint("0", theme.false_code),
+        1 => paint("1", theme.true_code),
         _ => unreachable!(),
     }
 }
 
-fn blue(s: &str) -> String {
-    format!("\x1b[1;34m{}\x1b[0m", s)
+fn print_truth_table_color(
+    formula: &str,
+    theme: &ColorTheme,
+    style: &TableStyle,
+) -> Result<(), ParseError> {
+    use std::io::BufWriter;
+    let out = std::io::stdout();
+    write_truth_table(&mut BufWriter::new(out.lock()), formula, theme, style)
 }
 
-fn print_truth_table_color(formula: &str, color: bool) -> Result<(), ParseError> {
-    use std::io::{BufWriter, Write};
+fn write_truth_table(
+    buf: &mut impl std::io::Write,
+    formula: &str,
+    theme: &ColorTheme,
+    style: &TableStyle,
+) -> Result<(), ParseError> {
     let tree = formula.parse::<Tree>()?;
     let var_list: Vec<char> = ('A'..='Z').filter(|&c| formula.contains(c)).collect();
-    let out = std::io::stdout();
-    let mut buf = BufWriter::new(out.lock());
-    let bar = if color { blue("|") } else { "|".to_string() };
+    let vertical = style.vertical;
+    let bar = paint(&vertical.to_string(), theme.header_code);
+    let cell = style.horizontal.to_string().repeat(3);
 
     writeln!(
         buf,
-        "{}{} = |",
+        "{}{} = {}",
         var_list
             .iter()
-            .map(|v| format!("| {} ", v))
+            .map(|v| format!("{} {} ", vertical, v))
             .collect::<String>(),
-        bar
+        bar,
+        vertical
     )
     .unwrap(); // | A | B | ... | Z | = |
-    writeln!(buf, "{}{}---|", ("|---").repeat(var_list.len()), bar).unwrap(); // |---|---| ... |---|
+    writeln!(
+        buf,
+        "{}{}{}{}{}",
+        style.left_joint,
+        vec![cell.clone(); var_list.len()].join(&style.cross.to_string()),
+        paint(&style.cross.to_string(), theme.header_code),
+        cell,
+        style.right_joint
+    )
+    .unwrap(); // |---|---| ... |---|
     for i in 0..(1u32 << var_list.len()) {
         for (j, v) in var_list.iter().enumerate() {
             let j = var_list.len() - j - 1;
@@ -64,77 +213,228 @@ fn print_truth_table_color(formula: &str, color: bool) -> Result<(), ParseError>
             tree.variables[*v as usize - 'A' as usize]
                 .borrow_mut()
                 .value = bit != 0;
-            write!(buf, "| {} ", color_bit(bit, color)).unwrap();
+            write!(buf, "{} {} ", vertical, color_bit(bit, theme)).unwrap();
         }
         writeln!(
             buf,
-            "{} {} |",
+            "{} {} {}",
             bar,
-            color_bit(tree.root.eval() as u32, color)
+            color_bit(tree.root.eval() as u32, theme),
+            vertical
         )
         .unwrap();
     }
     Ok(())
 }
 
-fn parse_args() -> Result<Args, String> {
-    let mut args = args();
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, ArgError> {
     let mut expr = String::new();
     let mut dot = false;
-    let mut color = false;
-    let path = args.next().unwrap_or_else(|| "ex04".to_string());
-
-    for arg in args {
-        if let Some(arg) = arg.strip_prefix('-') {
+    let mut theme = ColorTheme::default();
+    let mut style = TableStyle::default();
+    let mut format = Format::Ascii;
+    while let Some(arg) = args.next() {
+        if arg == "--format" {
+            format = Format::try_from(args.next().unwrap_or_default().as_str())?;
+        } else if arg == "--theme" {
+            theme = ColorTheme::try_from(args.next().unwrap_or_default().as_str())?;
+        } else if arg == "--style" {
+            style = TableStyle::try_from(args.next().unwrap_or_default().as_str())?;
+        } else if let Some(arg) = arg.strip_prefix('-').filter(|flags| !flags.is_empty()) {
             for c in arg.chars() {
                 match c {
                     'd' => dot = true,
-                    'c' => color = true,
+                    'c' => theme = ColorTheme::CLASSIC,
                     'r' => {
-                        if expr.is_empty() {
-                            expr = random_rpn_expr();
-                        } else {
-                            return Err(path);
+                        if !expr.is_empty() {
+                            return Err(ArgError::ConflictingRandom);
                         }
+                        expr = random_rpn_expr();
                     }
-                    _ => return Err(path),
+                    _ => return Err(ArgError::UnknownFlag(c)),
                 }
             }
         } else if expr.is_empty() {
+            // a lone "-" reads the formulas from stdin
             expr = arg;
         } else {
-            return Err(path);
+            return Err(ArgError::TooManyArgs);
         }
     }
     if expr.is_empty() {
-        Err(path)
+        Err(ArgError::MissingFormula)
     } else {
-        Ok(Args { expr, dot, color })
+        Ok(Args {
+            expr,
+            dot,
+            theme,
+            style,
+            format,
+        })
     }
 }
 
 fn main() -> Result<(), ParseError> {
-    let (expr, dot, color) = match parse_args() {
-        Ok(args) => (args.expr, args.dot, args.color),
-        Err(path) => {
-            println!("Usage: {} <formula | -r> [-c] [-d]", path);
+    let mut args = args();
+    let path = args.next().unwrap_or_else(|| "ex04".to_string());
+    let (expr, dot, theme, style, format) = match parse_args(args) {
+        Ok(args) => (args.expr, args.dot, args.theme, args.style, args.format),
+        Err(e) => {
+            println!("Error: {:?}", e);
+            println!(
+                "Usage: {} <formula | - | -r> [-c] [-d] [--format <format>] [--theme <theme>] [--style <style>]",
+                path
+            );
             println!("formula: a propositional boolean formula in rpn, ex: AB&C|");
             println!("Options:");
             println!("  -r  use a randomly generated formula");
+            println!("  -   read formulas from stdin, one per line");
             println!("  -c  color the truth table");
             println!("  -d  print the dot graph of the formula and generate an image from it");
+            println!("  --format  ascii (default), markdown, csv, json or latex");
+            println!(
+                "  --theme   colors of the ascii table: none (default), classic or colorblind"
+            );
+            println!("  --style   lines of the ascii table: ascii (default) or unicode");
             return Ok(());
         }
     };
+    if expr == "-" {
+        for line in stdin().lines().map_while(Result::ok) {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            println!("{}", line);
+            if let Err(e) = print_formula(line, &format, &theme, &style) {
+                println!("Error: {:?}", e);
+            }
+        }
+        return Ok(());
+    }
     println!("Input:\n{}", expr);
     let formula = expr.parse::<Tree>()?;
     if dot {
         create_graph(&formula.root);
     }
-    if color {
-        print_truth_table_color(&expr, color)?;
-    } else {
-        print_truth_table(&expr);
+    print_formula(&expr, &format, &theme, &style)
+}
+
+fn print_formula(
+    expr: &str,
+    format: &Format,
+    theme: &ColorTheme,
+    style: &TableStyle,
+) -> Result<(), ParseError> {
+    match format {
+        Format::Ascii if *theme != ColorTheme::NONE || *style != TableStyle::ASCII => {
+            print_truth_table_color(expr, theme, style)?
+        }
+        Format::Ascii => print_truth_table(expr),
+        Format::Markdown => print!("{}", export::to_markdown(expr)?),
+        Format::Csv => print!("{}", export::to_csv(expr)?),
+        Format::Json => print!("{}", export::to_json(expr)?),
+        Format::Latex => print!("{}", export::to_latex(expr)?),
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ex04_parse_args() {
+        let parse = |args: &[&str]| parse_args(args.iter().map(|s| s.to_string())).err();
+        arg_error::check_common_flags(parse);
+        assert_eq!(parse(&["AB&", "BA&"]), Some(ArgError::TooManyArgs));
+        assert_eq!(parse(&["-", "-d"]), None);
+        assert_eq!(parse(&["-", "AB&"]), Some(ArgError::TooManyArgs));
+        assert_eq!(parse(&["-", "-r"]), Some(ArgError::ConflictingRandom));
+        assert_eq!(parse(&["AB&", "--format", "csv"]), None);
+        assert_eq!(parse(&["AB&", "--theme", "colorblind"]), None);
+        assert_eq!(parse(&["AB&", "--style", "unicode"]), None);
+        assert_eq!(
+            parse(&["AB&", "--style", "round"]),
+            Some(ArgError::InvalidStyle("round".to_string()))
+        );
+        assert_eq!(
+            parse(&["AB&", "--theme", "pink"]),
+            Some(ArgError::InvalidTheme("pink".to_string()))
+        );
+        assert_eq!(
+            parse(&["AB&", "--format", "xml"]),
+            Some(ArgError::InvalidFormat("xml".to_string()))
+        );
+        assert_eq!(
+            parse(&["AB&", "--format"]),
+            Some(ArgError::InvalidFormat(String::new()))
+        );
+    }
+
+    #[test]
+    fn ex04_color_theme() {
+        let table = |theme: &ColorTheme| {
+            let mut out = Vec::new();
+            write_truth_table(&mut out, "AB|", theme, &TableStyle::ASCII).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        let plain = table(&ColorTheme::NONE);
+        assert!(!plain.contains('\x1b'));
+        assert!(plain.starts_with("| A | B | = |\n|---|---|---|\n| 0 | 0 | 0 |\n"));
+        for theme in [ColorTheme::CLASSIC, ColorTheme::COLORBLIND] {
+            let colored = table(&theme);
+            assert!(colored.contains('\x1b'));
+            // the same table once the escapes are gone
+            let mut stripped = String::new();
+            let mut chars = colored.chars();
+            while let Some(c) = chars.next() {
+                if c == '\x1b' {
+                    chars.by_ref().find(|&c| c == 'm');
+                } else {
+                    stripped.push(c);
+                }
+            }
+            assert_eq!(stripped, plain);
+        }
+        assert!(ColorTheme::default() == ColorTheme::NONE);
+    }
+
+    #[test]
+    fn ex04_export() {
+        assert_eq!(
+            export::to_markdown("AB&").unwrap(),
+            "| A | B | = |\n|---|---|---|\n| 0 | 0 | 0 |\n| 0 | 1 | 0 |\n| 1 | 0 | 0 |\n| 1 | 1 | 1 |\n"
+        );
+        assert_eq!(
+            export::to_json("AB|").unwrap(),
+            "{\"variables\":[\"A\",\"B\"],\"rows\":[[0,0,0],[0,1,1],[1,0,1],[1,1,1]]}\n"
+        );
+        assert_eq!(
+            export::to_latex("A!").unwrap(),
+            "\\begin{tabular}{|c|c|}\n\\hline\nA & $=$ \\\\\n\\hline\n0 & 1 \\\\\n1 & 0 \\\\\n\\hline\n\\end{tabular}\n"
+        );
+    }
+
+    #[test]
+    fn ex04_table_style() {
+        let table = |formula: &str, style: &TableStyle| {
+            let mut out = Vec::new();
+            write_truth_table(&mut out, formula, &ColorTheme::NONE, style).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+        assert_eq!(
+            table("AB&", &TableStyle::UNICODE),
+            "│ A │ B │ = │\n├───┼───┼───┤\n│ 0 │ 0 │ 0 │\n│ 0 │ 1 │ 0 │\n│ 1 │ 0 │ 0 │\n│ 1 │ 1 │ 1 │\n"
+        );
+        assert_eq!(
+            table("A!", &TableStyle::UNICODE),
+            "│ A │ = │\n├───┼───┤\n│ 0 │ 1 │\n│ 1 │ 0 │\n"
+        );
+        // every line is as wide as the header, counted in characters, not bytes
+        let unicode = table("ABC||", &TableStyle::UNICODE);
+        let width = unicode.lines().next().unwrap().chars().count();
+        assert!(unicode.lines().all(|line| line.chars().count() == width));
+        assert!(TableStyle::default() == TableStyle::ASCII);
+    }
+}
@@ -0,0 +1,78 @@
+// the truth table in formats meant for other tools, the last column is the result
+
+use crate::node::{ParseError, Tree};
+
+// every row of the table as bits, the variables then the result
+fn rows(formula: &str) -> Result<(Vec<char>, Vec<Vec<u8>>), ParseError> {
+    let tree = formula.parse::<Tree>()?;
+    let var_list: Vec<char> = ('A'..='Z').filter(|&c| formula.contains(c)).collect();
+    let rows = (0..(1u32 << var_list.len()))
+        .map(|i| {
+            let mut row: Vec<u8> = (0..var_list.len())
+                .map(|j| ((i >> (var_list.len() - j - 1)) & 1) as u8)
+                .collect();
+            for (v, &bit) in var_list.iter().zip(&row) {
+                tree.variables[*v as usize - 'A' as usize]
+                    .borrow_mut()
+                    .value = bit != 0;
+            }
+            row.push(tree.root.eval() as u8);
+            row
+        })
+        .collect();
+    Ok((var_list, rows))
+}
+
+fn join<T: ToString>(cells: impl IntoIterator<Item = T>, sep: &str) -> String {
+    cells
+        .into_iter()
+        .map(|c| c.to_string())
+        .collect::<Vec<_>>()
+        .join(sep)
+}
+
+// | A | B | = |, without colors so it renders anywhere
+pub fn to_markdown(formula: &str) -> Result<String, ParseError> {
+    let (var_list, rows) = rows(formula)?;
+    let mut out = format!("| {} | = |\n", join(&var_list, " | "));
+    out += &"|---".repeat(var_list.len() + 1);
+    out += "|\n";
+    for row in rows {
+        out += &format!("| {} |\n", join(row, " | "));
+    }
+    Ok(out)
+}
+
+pub fn to_csv(formula: &str) -> Result<String, ParseError> {
+    let (var_list, rows) = rows(formula)?;
+    let mut out = format!("{},=\n", join(&var_list, ","));
+    for row in rows {
+        out += &format!("{}\n", join(row, ","));
+    }
+    Ok(out)
+}
+
+// {"variables":["A","B"],"rows":[[0,0,0],...]}
+pub fn to_json(formula: &str) -> Result<String, ParseError> {
+    let (var_list, rows) = rows(formula)?;
+    let variables = join(var_list.iter().map(|v| format!("\"{}\"", v)), ",");
+    let rows = join(rows.iter().map(|row| format!("[{}]", join(row, ","))), ",");
+    Ok(format!(
+        "{{\"variables\":[{}],\"rows\":[{}]}}\n",
+        variables, rows
+    ))
+}
+
+pub fn to_latex(formula: &str) -> Result<String, ParseError> {
+    let (var_list, rows) = rows(formula)?;
+    let mut out = format!(
+        "\\begin{{tabular}}{{|{}|c|}}\n\\hline\n",
+        "c".repeat(var_list.len())
+    );
+    out += &format!("{} & $=$ \\\\\n\\hline\n", join(&var_list, " & "));
+    for row in rows {
+        out += &format!("{} \\\\\n", join(row, " & "));
+    }
+    out += "\\hline\n\\end{tabular}\n";
+    Ok(out)
+}
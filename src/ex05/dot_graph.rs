@@ -68,6 +68,10 @@ fn get_idx(node: &Node, idx: &mut HashMap<char, usize>) -> String {
         s
     };
     match node {
+        Const(c) => {
+            let id = get_id('c');
+            format!("\"{}_{}\"", (*c as u8), id)
+        }
         Val(v) => {
             let v = v.get().name;
             let id = get_id(v);
@@ -87,6 +91,9 @@ fn get_idx(node: &Node, idx: &mut HashMap<char, usize>) -> String {
 fn print_dot_node(dot: &mut String, node: &Node, idx: &mut HashMap<char, usize>) -> String {
     let id = get_idx(node, idx);
     match node {
+        Const(c) => {
+            dot.push_str(&format!("\t{} [label=\"{}\"];\n", id, (*c as u8)));
+        }
         Val(v) => {
             let v = v.get().name;
             dot.push_str(&format!("\t{} [label=\"{}\"];\n", id, v));
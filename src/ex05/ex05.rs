@@ -1,14 +1,19 @@
 // an AST to parse logical expressions in rpn
 
+#[allow(dead_code)]
+#[path = "../arg_error.rs"]
+mod arg_error;
 mod dot_graph;
 mod expr_generator;
 mod node;
 
 use crate::node::Tree;
+use arg_error::ArgError;
 use dot_graph::create_graph;
 use expr_generator::random_rpn_expr;
 use node::ParseError;
 use std::env::args;
+use std::io::stdin;
 
 struct Args {
     expr: String,
@@ -22,52 +27,62 @@ fn negation_normal_form(formula: &str) -> String {
     }
 }
 
-fn parse_args() -> Result<Args, String> {
-    let mut args = args();
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Args, ArgError> {
     let mut expr = String::new();
     let mut dot = false;
-    let path = args.next().unwrap_or_else(|| "ex05".to_string());
-
     for arg in args {
-        if let Some(arg) = arg.strip_prefix('-') {
+        if let Some(arg) = arg.strip_prefix('-').filter(|flags| !flags.is_empty()) {
             for c in arg.chars() {
                 match c {
                     'd' => dot = true,
                     'r' => {
-                        if expr.is_empty() {
-                            expr = random_rpn_expr();
-                        } else {
-                            return Err(path);
+                        if !expr.is_empty() {
+                            return Err(ArgError::ConflictingRandom);
                         }
+                        expr = random_rpn_expr();
                     }
-                    _ => return Err(path),
+                    _ => return Err(ArgError::UnknownFlag(c)),
                 }
             }
         } else if expr.is_empty() {
+            // a lone "-" reads the formulas from stdin
             expr = arg;
         } else {
-            return Err(path);
+            return Err(ArgError::TooManyArgs);
         }
     }
     if expr.is_empty() {
-        Err(path)
+        Err(ArgError::MissingFormula)
     } else {
         Ok(Args { expr, dot })
     }
 }
 
 fn main() -> Result<(), ParseError> {
-    let (expr, dot) = match parse_args() {
+    let mut args = args();
+    let path = args.next().unwrap_or_else(|| "ex05".to_string());
+    let (expr, dot) = match parse_args(args) {
         Ok(args) => (args.expr, args.dot),
-        Err(path) => {
-            println!("Usage: {} <formula | -r> [-d]", path);
+        Err(e) => {
+            println!("Error: {:?}", e);
+            println!("Usage: {} <formula | - | -r> [-d]", path);
             println!("formula: a propositional boolean formula in rpn, ex: AB&C|");
             println!("Options:");
             println!("  -r  use a randomly generated formula");
+            println!("  -   read formulas from stdin, one per line");
             println!("  -d  print the dot graph of the formula and generate an image from it");
             return Ok(());
         }
     };
+    if expr == "-" {
+        for line in stdin().lines().map_while(Result::ok) {
+            let line = line.trim();
+            if !line.is_empty() {
+                println!("{}", negation_normal_form(line));
+            }
+        }
+        return Ok(());
+    }
     println!("Input:\n{}", expr);
     let tree = expr.parse::<Tree>()?.root;
     if dot {
@@ -77,3 +92,76 @@ fn main() -> Result<(), ParseError> {
     println!("{}", negation_normal_form(&expr));
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::node::BinOp::*;
+    use crate::node::{Node, Var};
+
+    impl Node {
+        fn eval(&self) -> bool {
+            match self {
+                Node::Val(v) => v.get().value,
+                Node::Const(c) => *c,
+                Node::Not { operand } => !operand.eval(),
+                Node::Binary { op, left, right } => {
+                    let left = left.eval();
+                    let right = right.eval();
+                    match op {
+                        And => left && right,
+                        Or => left || right,
+                        Impl => !left || right,
+                        Leq => left == right,
+                        Xor => left ^ right,
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn ex05_parse_args() {
+        let parse = |args: &[&str]| parse_args(args.iter().map(|s| s.to_string())).err();
+        arg_error::check_common_flags(parse);
+        assert_eq!(parse(&["AB&", "BA&"]), Some(ArgError::TooManyArgs));
+        assert_eq!(parse(&["-", "-d"]), None);
+        assert_eq!(parse(&["-", "AB&"]), Some(ArgError::TooManyArgs));
+        assert_eq!(parse(&["-", "-r"]), Some(ArgError::ConflictingRandom));
+    }
+
+    #[test]
+    fn ex05_to_nnf() {
+        for expr in [
+            "AB&!", "AB|C>!", "AB^C=", "AB=!", "ABC^^!", "A!!B>", "AB>C&!D|", "A1^", "A0=B|!",
+        ] {
+            let tree = expr.parse::<Tree>().expect("input is valid");
+            let nnf = tree.to_nnf();
+            assert_eq!(tree.root.to_string(), expr);
+            assert_eq!(nnf.root.to_string(), negation_normal_form(expr));
+            // the variables are shared, so one assignment drives both
+            for row in 0..16 {
+                for (i, cell) in tree.variables.iter().enumerate().take(4) {
+                    let name = cell.get().name;
+                    cell.set(Var {
+                        name,
+                        value: (row >> i) & 1 == 1,
+                    });
+                }
+                assert_eq!(nnf.root.eval(), tree.root.eval(), "{}", expr);
+            }
+        }
+    }
+
+    #[test]
+    fn ex05_constants() {
+        assert!("A1&".parse::<Tree>().is_ok());
+        assert_eq!(negation_normal_form("A0|"), "A");
+        assert_eq!(negation_normal_form("A1&"), "A");
+        assert_eq!(negation_normal_form("A0&B|"), "B");
+        assert_eq!(negation_normal_form("A1|!"), "0");
+        assert_eq!(negation_normal_form("1!A>"), "1");
+        assert_eq!(negation_normal_form("AB0|&!"), "A!B!|");
+        assert_eq!(negation_normal_form("0"), "0");
+    }
+}
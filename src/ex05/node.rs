@@ -31,6 +31,7 @@ pub enum Node {
         operand: Box<Node>,
     },
     Val(Rc<Cell<Var>>),
+    Const(bool),
 }
 
 pub struct Tree {
@@ -84,6 +85,7 @@ impl fmt::Display for Node {
             Binary { op, left, right } => write!(f, "{}{}{}", left, right, op),
             Not { operand } => write!(f, "{}!", operand),
             Val(val) => write!(f, "{}", val.get().name),
+            Const(val) => write!(f, "{}", *val as u8),
         }
     }
 }
@@ -116,6 +118,7 @@ impl std::str::FromStr for Tree {
                 'A'..='Z' => {
                     stack.push(Val(variables[c as usize - b'A' as usize].clone()));
                 }
+                '0' | '1' => stack.push(Const(c == '1')),
                 '!' => {
                     let operand = stack.pop().ok_or(MissingOperand)?;
                     stack.push(Not {
@@ -185,10 +188,35 @@ impl std::ops::Not for Node {
     }
 }
 
+impl Tree {
+    // the nnf as a new tree, sharing the variables and leaving this one as is
+    #[allow(dead_code)]
+    pub fn to_nnf(&self) -> Tree {
+        Tree {
+            root: *self.root.clone().nnf(),
+            variables: self.variables.clone(),
+        }
+    }
+}
+
+// an & or | of two nnf, with the constants folded away
+fn fold(op: BinOp, left: Box<Node>, right: Box<Node>) -> Box<Node> {
+    match (op, &*left, &*right) {
+        (And, Const(false), _) | (And, _, Const(false)) => Box::new(Const(false)),
+        (Or, Const(true), _) | (Or, _, Const(true)) => Box::new(Const(true)),
+        // the neutral element, 1 for & and 0 for |
+        (_, Const(_), _) => right,
+        (_, _, Const(_)) => left,
+        (And, ..) => left & right,
+        _ => left | right,
+    }
+}
+
 impl Node {
     pub fn nnf(self) -> Box<Node> {
         match self {
             Val(v) => Box::new(Val(v)),
+            Const(c) => Box::new(Const(c)),
             Binary { op, left, right } => match op {
                 // Xor -> (!A & B ) | (A & !B)
                 Xor => ((left.clone() & !right.clone()) | (!left & right)).nnf(),
@@ -196,11 +224,12 @@ impl Node {
                 Impl => (!left | right).nnf(),
                 // Leq == (A & B) | (!A & !B)
                 Leq => ((left.clone() & right.clone()) | (!left & !right)).nnf(),
-                And => left.nnf() & right.nnf(),
-                Or => left.nnf() | right.nnf(),
+                And => fold(And, left.nnf(), right.nnf()),
+                Or => fold(Or, left.nnf(), right.nnf()),
             },
             Not { operand } => match *operand {
                 Val(v) => !Val(v),
+                Const(c) => Box::new(Const(!c)),
                 Not { operand } => (*operand).nnf(),
                 Binary { op, left, right } => match op {
                     // !(A & B) -> !A | !B
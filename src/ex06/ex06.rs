@@ -1,14 +1,19 @@
 // an AST to parse logical expressions in rpn
 
+#[allow(dead_code)]
+#[path = "../arg_error.rs"]
+mod arg_error;
 mod dot_graph;
 mod expr_generator;
 mod node;
 
 use crate::node::Tree;
+use arg_error::ArgError;
 use dot_graph::create_graph;
 use expr_generator::random_rpn_expr;
 use node::ParseError;
 use std::env::args;
+use std::io::stdin;
 
 struct Args {
     expr: String,
@@ -22,52 +27,62 @@ fn conjunctive_normal_form(formula: &str) -> String {
     }
 }
 
-fn parse_args() -> Result<Args, String> {
-    let mut args = args();
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Args, ArgError> {
     let mut expr = String::new();
     let mut dot = false;
-    let path = args.next().unwrap_or_else(|| "ex06".to_string());
-
     for arg in args {
-        if let Some(arg) = arg.strip_prefix('-') {
+        if let Some(arg) = arg.strip_prefix('-').filter(|flags| !flags.is_empty()) {
             for c in arg.chars() {
                 match c {
                     'd' => dot = true,
                     'r' => {
-                        if expr.is_empty() {
-                            expr = random_rpn_expr(3, 5);
-                        } else {
-                            return Err(path);
+                        if !expr.is_empty() {
+                            return Err(ArgError::ConflictingRandom);
                         }
+                        expr = random_rpn_expr(3, 5);
                     }
-                    _ => return Err(path),
+                    _ => return Err(ArgError::UnknownFlag(c)),
                 }
             }
         } else if expr.is_empty() {
+            // a lone "-" reads the formulas from stdin
             expr = arg;
         } else {
-            return Err(path);
+            return Err(ArgError::TooManyArgs);
         }
     }
     if expr.is_empty() {
-        Err(path)
+        Err(ArgError::MissingFormula)
     } else {
         Ok(Args { expr, dot })
     }
 }
 
 fn main() -> Result<(), ParseError> {
-    let (expr, dot) = match parse_args() {
+    let mut args = args();
+    let path = args.next().unwrap_or_else(|| "ex06".to_string());
+    let (expr, dot) = match parse_args(args) {
         Ok(args) => (args.expr, args.dot),
-        Err(path) => {
-            println!("Usage: {} <formula | -r> [-d]", path);
+        Err(e) => {
+            println!("Error: {:?}", e);
+            println!("Usage: {} <formula | - | -r> [-d]", path);
             println!("formula: a propositional boolean formula in rpn, ex: AB&C|");
             println!("Options:");
             println!("  -r  use a randomly generated formula");
+            println!("  -   read formulas from stdin, one per line");
             println!("  -d  print the dot graph of the formula and generate an image from it");
             return Ok(());
         }
     };
+    if expr == "-" {
+        for line in stdin().lines().map_while(Result::ok) {
+            let line = line.trim();
+            if !line.is_empty() {
+                println!("{}", conjunctive_normal_form(line));
+            }
+        }
+        return Ok(());
+    }
     println!("Input:\n{}", expr);
     let tree = expr.parse::<Tree>()?.root;
     if dot {
@@ -92,10 +107,11 @@ mod tests {
         assert_eq!(cnf, expected, "formula: {}", formula);
     }
 
+    // the table of `input` over the variables of the formula `vars`
     #[allow(dead_code)]
     fn get_table(input: &str, vars: &str) -> Vec<bool> {
         let tree = input.parse::<Tree>().expect("input is valid");
-        let var_list: Vec<char> = ('A'..='Z').filter(|&c| vars.contains(c)).collect();
+        let var_list = vars.parse::<Tree>().expect("input is valid").varlist();
         let mut res = Vec::with_capacity(1 << var_list.len());
         for i in 0..(1 << var_list.len()) {
             for (j, v) in var_list.iter().enumerate() {
@@ -116,20 +132,19 @@ mod tests {
     }
 
     impl Node {
-        #[allow(dead_code)]
-        fn eval(&self) -> bool {
+        // every operand evaluated, the reference for eval's short-circuits
+        fn eval_strict(&self) -> bool {
             let res = match &self.literal {
                 Const(c) => *c,
                 Var(v) => v.get().value,
                 Binary { op, children } => {
-                    let left = children[0].eval();
-                    let right = children[1].eval();
+                    let values: Vec<bool> = children.iter().map(Node::eval_strict).collect();
                     match op {
-                        And => left && right,
-                        Or => left || right,
-                        Impl => !left || right,
-                        Leq => left == right,
-                        Xor => left ^ right,
+                        And => values.iter().all(|&v| v),
+                        Or => values.iter().any(|&v| v),
+                        Xor => values.iter().fold(false, |res, &v| res ^ v),
+                        Impl => !values[0] || values[1],
+                        Leq => values[0] == values[1],
                     }
                 }
             };
@@ -171,4 +186,118 @@ mod tests {
             assert_eq!(get_table(&simp, &expr), get_table(&expr, &expr), "{}", expr);
         }
     }
+
+    #[test]
+    fn ex06_cnf_with_constants() {
+        test_cnf("A1&", "A");
+        test_cnf("A0|", "A");
+        assert_eq!("A1&B0|&".parse::<Tree>().unwrap().varlist(), ['A', 'B']);
+        for expr in ["A1&", "A0|", "A0&B|", "1A>B^", "AB=0|"] {
+            let cnf = conjunctive_normal_form(expr);
+            assert_eq!(get_table(&cnf, expr), get_table(expr, expr), "{}", expr);
+        }
+    }
+
+    #[test]
+    fn ex06_parse_args() {
+        let parse = |args: &[&str]| parse_args(args.iter().map(|s| s.to_string())).err();
+        arg_error::check_common_flags(parse);
+        assert_eq!(parse(&["AB&", "BA&"]), Some(ArgError::TooManyArgs));
+        assert_eq!(parse(&["-", "-d"]), None);
+        assert_eq!(parse(&["-", "AB&"]), Some(ArgError::TooManyArgs));
+        assert_eq!(parse(&["-", "-r"]), Some(ArgError::ConflictingRandom));
+    }
+
+    #[test]
+    fn ex06_binarize() {
+        fn depth(node: &Node) -> usize {
+            match &node.literal {
+                Binary { children, .. } => {
+                    assert_eq!(children.len(), 2);
+                    1 + children.iter().map(depth).max().unwrap()
+                }
+                _ => 0,
+            }
+        }
+        let tree = "AB|".parse::<Tree>().expect("input is valid");
+        let var = |c: char| Node {
+            not: 0,
+            literal: Var(tree.variables[c as usize - 'A' as usize].clone()),
+        };
+        let or = Node {
+            not: 1,
+            literal: Binary {
+                op: Or,
+                children: "ABCD".chars().map(var).collect(),
+            },
+        };
+        assert_eq!(or.to_string(), "ABCD|||!");
+        let binary = or.binarize();
+        assert_eq!(depth(&binary), 2);
+        assert_eq!(binary.to_string(), "AB|CD||!");
+        let or = or.to_string();
+        assert_eq!(get_table(&binary.to_string(), &or), get_table(&or, &or));
+    }
+
+    #[test]
+    fn ex06_to_clauses() {
+        let clauses = |expr: &str| expr.parse::<Tree>().expect("input is valid").to_clauses();
+        assert_eq!(clauses("AB&!"), [[('A', false), ('B', false)]]);
+        assert_eq!(
+            clauses("AB|C&"),
+            [vec![('A', true), ('B', true)], vec![('C', true)]]
+        );
+        assert_eq!(clauses("A!"), [[('A', false)]]);
+        assert_eq!(clauses("AA!|"), Vec::<Vec<(char, bool)>>::new());
+        assert_eq!(clauses("AA!&"), [Vec::<(char, bool)>::new()]);
+        // simplify already drops a clause with a variable and its negation
+        assert_eq!(clauses("AA!|B|C&"), [[('C', true)]]);
+    }
+
+    #[test]
+    fn ex06_eval_short_circuit() {
+        let reads = |expr: &str, values: &[(char, bool)]| {
+            let tree = expr.parse::<Tree>().expect("input is valid");
+            for &(name, value) in values {
+                tree.set_var(name, value);
+            }
+            let mut reads = 0;
+            let res = tree.root.eval_with(&mut |v| {
+                reads += 1;
+                v.get().value
+            });
+            (res, reads)
+        };
+        assert_eq!(reads("AB&C&", &[('A', false)]), (false, 1));
+        assert_eq!(reads("AB|C|", &[('A', true)]), (true, 1));
+        assert_eq!(reads("AB&C|", &[('A', true), ('B', true)]), (true, 2));
+        assert_eq!(reads("AB^", &[('A', true)]), (true, 2));
+        assert_eq!(reads("AB>", &[('A', false)]), (true, 2));
+        assert_eq!(reads("AB=", &[]), (true, 2));
+        // the n-ary operators of a cnf stop early too
+        let cnf = "AB|C&D&"
+            .parse::<Tree>()
+            .expect("input is valid")
+            .root
+            .cnf();
+        let mut reads = 0;
+        assert!(!cnf.eval_with(&mut |v| {
+            reads += 1;
+            v.get().value
+        }));
+        assert_eq!(reads, 2);
+        for _ in 0..200 {
+            let expr = random_rpn_expr(3, 5);
+            let tree = expr.parse::<Tree>().expect("input is valid");
+            let cnf = tree.root.clone().cnf().simplify();
+            let vars = tree.varlist();
+            for row in 0..1 << vars.len() {
+                for (j, &v) in vars.iter().enumerate() {
+                    tree.set_var(v, (row >> j) & 1 == 1);
+                }
+                assert_eq!(tree.root.eval(), tree.root.eval_strict(), "{}", expr);
+                assert_eq!(cnf.eval(), cnf.eval_strict(), "{}", expr);
+            }
+        }
+    }
 }
@@ -59,6 +59,8 @@ impl PartialEq for Literal {
     }
 }
 
+// literals of different kinds are unordered here, and only Ord treats them as equal
+#[allow(clippy::non_canonical_partial_ord_impl)]
 impl PartialOrd for Literal {
     fn partial_cmp(&self, other: &Literal) -> Option<std::cmp::Ordering> {
         match (self, other) {
@@ -172,7 +174,7 @@ impl fmt::Display for Node {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{}", self.literal)?;
         if self.not > 0 {
-            write!(f, "{}", "!".repeat(self.not as usize))
+            write!(f, "{}", "!".repeat(self.not))
         } else {
             Ok(())
         }
@@ -313,7 +315,107 @@ impl Node {
     }
 }
 
+// the children split in halves until they come in pairs
+#[allow(dead_code)]
+fn balanced(op: BinOp, children: &[Node]) -> Node {
+    match children {
+        [] => Node {
+            not: 0,
+            literal: Const(op == And),
+        },
+        [child] => child.binarize(),
+        _ => {
+            let (left, right) = children.split_at(children.len() / 2);
+            new_binary(op, vec![balanced(op, left), balanced(op, right)])
+        }
+    }
+}
+
+impl Tree {
+    // the variables appearing in the formula, sorted, constants aside
+    #[allow(dead_code)]
+    pub fn varlist(&self) -> Vec<char> {
+        let mut vars = Vec::new();
+        self.root.collect_vars(&mut vars);
+        vars.sort_unstable();
+        vars.dedup();
+        vars
+    }
+
+    // the clauses of the cnf, read off the n-ary tree, with whether each variable is positive
+    // a true formula has no clause, a false one has a single empty clause
+    #[allow(dead_code)]
+    pub fn to_clauses(&self) -> Vec<Vec<(char, bool)>> {
+        let cnf = self.root.clone().cnf().simplify();
+        match &cnf.literal {
+            Const(c) if *c ^ (cnf.not % 2 == 1) => vec![],
+            Const(_) => vec![vec![]],
+            Binary { op: And, children } => children.iter().map(Node::clause).collect(),
+            _ => vec![cnf.clause()],
+        }
+    }
+}
+
 impl Node {
+    // the literals of a disjunction of variables, or of a single one
+    fn clause(&self) -> Vec<(char, bool)> {
+        match &self.literal {
+            Var(v) => vec![(v.get().name, self.not.is_multiple_of(2))],
+            Binary { op: Or, children } => children.iter().flat_map(Node::clause).collect(),
+            _ => unreachable!("not a clause: {}", self),
+        }
+    }
+
+    // evaluates under the current values, & and | stop at the first operand that decides
+    #[allow(dead_code)]
+    pub fn eval(&self) -> bool {
+        self.eval_with(&mut |v| v.get().value)
+    }
+
+    // the same, reading the variables through `value`, so a test can watch the reads
+    #[allow(dead_code)]
+    pub fn eval_with(&self, value: &mut impl FnMut(&VarCell) -> bool) -> bool {
+        let res = match &self.literal {
+            Const(c) => *c,
+            Var(v) => value(v),
+            Binary { op, children } => match op {
+                And => children.iter().all(|c| c.eval_with(value)),
+                Or => children.iter().any(|c| c.eval_with(value)),
+                Xor => children
+                    .iter()
+                    .fold(false, |res, c| res ^ c.eval_with(value)),
+                Impl => {
+                    let left = children[0].eval_with(value);
+                    let right = children[1].eval_with(value);
+                    !left || right
+                }
+                Leq => children[0].eval_with(value) == children[1].eval_with(value),
+            },
+        };
+        res ^ !self.not.is_multiple_of(2)
+    }
+
+    pub fn collect_vars(&self, vars: &mut Vec<char>) {
+        match &self.literal {
+            Const(_) => (),
+            Var(v) => vars.push(v.get().name),
+            Binary { children, .. } => children.iter().for_each(|c| c.collect_vars(vars)),
+        }
+    }
+
+    // rewrites every n-ary operator as a balanced tree of binary ones, ex: ABCD||| -> AB|CD||
+    #[allow(dead_code)]
+    pub fn binarize(&self) -> Node {
+        match &self.literal {
+            Const(_) | Var(_) => self.clone(),
+            Binary { op, children } => {
+                let mut new = balanced(*op, children);
+                new.not += self.not;
+                new
+            }
+        }
+    }
+
     pub fn cnf(self) -> Node {
         let mut new = self.clone();
         new.not = self.not % 2;
@@ -0,0 +1,103 @@
+// evaluates a formula once, then only re-evaluates the path from a toggled variable to the root
+
+use crate::node::{BinOp, Node, Token, VarCell, Variable};
+
+enum Op {
+    Const(bool),
+    Var,
+    Not(usize),
+    Binary {
+        op: BinOp,
+        left: usize,
+        right: usize,
+    },
+}
+
+pub struct CachedEval {
+    // the tree flattened in post-order, so the root is the last one
+    ops: Vec<Op>,
+    values: Vec<bool>,
+    // the root is its own parent
+    parents: Vec<usize>,
+    // indices of the Var ops, for each variable
+    leaves: Vec<Vec<usize>>,
+    variables: Vec<VarCell>,
+}
+
+impl CachedEval {
+    // the root's variables must be cells from `variables`
+    pub fn new(root: &Node, variables: &[VarCell]) -> CachedEval {
+        let mut cached = CachedEval {
+            ops: Vec::new(),
+            values: Vec::new(),
+            parents: Vec::new(),
+            leaves: vec![Vec::new(); 26],
+            variables: variables.to_vec(),
+        };
+        // the rpn is the post-order, flattened without recursing on deep trees
+        let mut operands = Vec::new();
+        for token in root.to_postfix_tokens() {
+            let i = cached.ops.len();
+            let (op, value) = match token {
+                Token::Const(c) => (Op::Const(c), c),
+                Token::Var(name) => {
+                    let idx = name as usize - 'A' as usize;
+                    cached.leaves[idx].push(i);
+                    (Op::Var, cached.variables[idx].get().value)
+                }
+                Token::Not => {
+                    let n = operands.pop().unwrap();
+                    cached.parents[n] = i;
+                    (Op::Not(n), !cached.values[n])
+                }
+                Token::Op(op) => {
+                    let right = operands.pop().unwrap();
+                    let left = operands.pop().unwrap();
+                    cached.parents[left] = i;
+                    cached.parents[right] = i;
+                    let value = op.apply(cached.values[left], cached.values[right]);
+                    (Op::Binary { op, left, right }, value)
+                }
+            };
+            cached.ops.push(op);
+            cached.values.push(value);
+            cached.parents.push(i);
+            operands.push(i);
+        }
+        cached
+    }
+
+    fn compute(&self, i: usize) -> bool {
+        match self.ops[i] {
+            Op::Const(c) => c,
+            Op::Var => self.values[i],
+            Op::Not(n) => !self.values[n],
+            Op::Binary { op, left, right } => op.apply(self.values[left], self.values[right]),
+        }
+    }
+
+    pub fn value(&self) -> bool {
+        self.values[self.values.len() - 1]
+    }
+
+    // flips a variable (in the tree too) and returns the new value of the formula
+    pub fn toggle(&mut self, name: char) -> bool {
+        let idx = name as usize - 'A' as usize;
+        let value = !self.variables[idx].get().value;
+        self.variables[idx].set(Variable { name, value });
+        for k in 0..self.leaves[idx].len() {
+            let mut i = self.leaves[idx][k];
+            self.values[i] = value;
+            // walk up until a node doesn't change, its ancestors won't either
+            while self.parents[i] != i {
+                i = self.parents[i];
+                let new = self.compute(i);
+                if new == self.values[i] {
+                    break;
+                }
+                self.values[i] = new;
+            }
+        }
+        self.value()
+    }
+}
@@ -0,0 +1,105 @@
+// builds a cnf tree one clause at a time
+
+use crate::node::{new_variables, BinOp, Node, Tree, VarCell};
+use std::fmt;
+use ClauseError::*;
+
+pub struct CnfBuilder {
+    variables: Vec<VarCell>,
+    clauses: Vec<Node>,
+}
+
+// the literals of a clause sorted by variable, each once, never both signs of a variable
+#[derive(PartialEq, Eq, Hash)]
+pub struct Clause(Vec<(char, bool)>);
+
+#[derive(PartialEq, Eq)]
+pub enum ClauseError {
+    // holds a variable and its negation, true whatever the values
+    Tautology(char),
+    InvalidVariable(char),
+}
+
+impl fmt::Debug for ClauseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Tautology(v) => write!(f, "Tautology on {}", v),
+            InvalidVariable(v) => write!(f, "Invalid variable: '{}'", v),
+        }
+    }
+}
+
+impl TryFrom<&[(char, bool)]> for Clause {
+    type Error = ClauseError;
+
+    fn try_from(literals: &[(char, bool)]) -> Result<Self, Self::Error> {
+        if let Some(&(v, _)) = literals.iter().find(|(v, _)| !v.is_ascii_uppercase()) {
+            return Err(InvalidVariable(v));
+        }
+        let mut literals = literals.to_vec();
+        literals.sort_unstable();
+        literals.dedup();
+        match literals.windows(2).find(|pair| pair[0].0 == pair[1].0) {
+            Some(pair) => Err(Tautology(pair[0].0)),
+            None => Ok(Clause(literals)),
+        }
+    }
+}
+
+impl Clause {
+    #[allow(dead_code)]
+    pub fn literals(&self) -> &[(char, bool)] {
+        &self.0
+    }
+}
+
+fn join(op: BinOp) -> impl Fn(Node, Node) -> Node {
+    move |left, right| Node::Binary {
+        op,
+        left: Box::new(left),
+        right: Box::new(right),
+    }
+}
+
+impl CnfBuilder {
+    pub fn new() -> CnfBuilder {
+        CnfBuilder {
+            variables: new_variables(),
+            clauses: Vec::new(),
+        }
+    }
+
+    // adds a normalized clause, see add_clause
+    pub fn add(&mut self, clause: &Clause) {
+        self.add_clause(&clause.0);
+    }
+
+    // ors the literals, each a variable and whether it's positive
+    // an empty clause is false, and a variable not in A-Z panics, Clause checks them first
+    pub fn add_clause(&mut self, literals: &[(char, bool)]) {
+        let clause = literals
+            .iter()
+            .map(|&(v, positive)| {
+                assert!(v.is_ascii_uppercase(), "{:?} is not a variable", v);
+                let var = Node::Var(self.variables[v as usize - 'A' as usize].clone());
+                if positive {
+                    var
+                } else {
+                    Node::Not(Box::new(var))
+                }
+            })
+            .reduce(join(BinOp::Or))
+            .unwrap_or(Node::Const(false));
+        self.clauses.push(clause);
+    }
+
+    // ands the clauses, no clause at all is true
+    pub fn build(self) -> Tree {
+        let root = self
+            .clauses
+            .into_iter()
+            .reduce(join(BinOp::And))
+            .unwrap_or(Node::Const(true));
+        Tree::from_root(root, self.variables)
+    }
+}
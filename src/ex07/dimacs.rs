@@ -0,0 +1,127 @@
+// reads formulas in the DIMACS CNF format used by SAT benchmarks
+// variables 1 to 26 are mapped to A to Z
+
+use crate::cnf_builder::{Clause, CnfBuilder};
+use crate::node::Tree;
+use std::collections::HashSet;
+use std::fmt;
+use std::io::{self, Write};
+use DimacsError::*;
+
+#[derive(PartialEq, Eq)]
+pub enum DimacsError {
+    InvalidHeader(String),
+    InvalidLiteral(String),
+    VariableOutOfRange(u64),
+}
+
+impl fmt::Debug for DimacsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InvalidHeader(line) => write!(f, "Invalid header: '{}'", line),
+            InvalidLiteral(lit) => write!(f, "Invalid literal: '{}'", lit),
+            VariableOutOfRange(var) => write!(f, "Variable out of range (1-26): {}", var),
+        }
+    }
+}
+
+fn parse_clauses(input: &str) -> Result<Vec<Vec<i64>>, DimacsError> {
+    let mut clauses = Vec::new();
+    let mut clause = Vec::new();
+    for line in input.lines() {
+        let line = line.trim();
+        if line.starts_with('c') || line.is_empty() {
+            continue;
+        }
+        if line.starts_with('p') {
+            let header: Vec<&str> = line.split_whitespace().collect();
+            if header.len() != 4 || header[1] != "cnf" {
+                return Err(InvalidHeader(line.to_string()));
+            }
+            continue;
+        }
+        // some benchmark files end with a '%' line
+        if line.starts_with('%') {
+            break;
+        }
+        for lit in line.split_whitespace() {
+            let lit: i64 = lit.parse().map_err(|_| InvalidLiteral(lit.to_string()))?;
+            if lit == 0 {
+                clauses.push(std::mem::take(&mut clause));
+            } else if lit.unsigned_abs() > 26 {
+                return Err(VariableOutOfRange(lit.unsigned_abs()));
+            } else {
+                clause.push(lit);
+            }
+        }
+    }
+    // be lenient with a last clause missing its 0
+    if !clause.is_empty() {
+        clauses.push(clause);
+    }
+    Ok(clauses)
+}
+
+// with `clean`, repeated clauses and the ones holding a literal and its negation are dropped,
+// the count of dropped clauses comes with the tree
+pub fn from_dimacs(input: &str, clean: bool) -> Result<(Tree, usize), DimacsError> {
+    let mut builder = CnfBuilder::new();
+    let mut seen = HashSet::new();
+    let mut removed = 0;
+    for clause in parse_clauses(input)? {
+        let literals: Vec<(char, bool)> = clause
+            .into_iter()
+            .map(|lit| ((b'A' + lit.unsigned_abs() as u8 - 1) as char, lit > 0))
+            .collect();
+        if !clean {
+            builder.add_clause(&literals);
+            continue;
+        }
+        // the range was checked while parsing, only a tautology is refused
+        match Clause::try_from(literals.as_slice()) {
+            Ok(clause) if !seen.contains(&clause) => {
+                builder.add(&clause);
+                seen.insert(clause);
+            }
+            _ => removed += 1,
+        }
+    }
+    Ok((builder.build(), removed))
+}
+
+pub fn satisfy_dimacs_file(path: &str) -> io::Result<bool> {
+    let input = std::fs::read_to_string(path)?;
+    let (tree, _) = from_dimacs(&input, false)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, format!("{:?}", e)))?;
+    Ok(tree.satisfy())
+}
+
+impl Tree {
+    // the cnf of the formula in DIMACS, A being 1
+    pub fn to_dimacs(&self) -> String {
+        let mut out = Vec::new();
+        self.write_dimacs(&mut out)
+            .expect("writing to a Vec doesn't fail");
+        String::from_utf8(out).expect("DIMACS is ascii")
+    }
+
+    // streams the cnf of the formula in DIMACS, A being 1
+    pub fn write_dimacs<W: Write>(&self, out: &mut W) -> io::Result<()> {
+        let clauses = self.cnf_clauses();
+        let num_vars = clauses
+            .iter()
+            .flatten()
+            .map(|&(v, _)| v as usize - 'A' as usize + 1)
+            .max()
+            .unwrap_or(0);
+        writeln!(out, "p cnf {} {}", num_vars, clauses.len())?;
+        for clause in clauses {
+            for (v, positive) in clause {
+                let var = v as usize - 'A' as usize + 1;
+                write!(out, "{}{} ", if positive { "" } else { "-" }, var)?;
+            }
+            writeln!(out, "0")?;
+        }
+        Ok(())
+    }
+}
@@ -18,14 +18,7 @@ pub fn create_graph(node: &Node, target: &str) {
             return;
         }
     };
-    let mut dot = String::new();
-    let mut idx = HashMap::new();
-    dot.push_str("digraph {\n");
-    dot.push_str("\tnode [shape=none];\n");
-    dot.push_str("\tedge [arrowhead=none];\n");
-    dot.push('\n');
-    print_dot_node(&mut dot, node, &mut idx);
-    dot.push('}');
+    let dot = to_dot(node);
     match file.write_all(dot.as_bytes()) {
         Ok(_) => println!("Created dot file {}", dot_target),
         Err(e) => {
@@ -38,11 +31,124 @@ pub fn create_graph(node: &Node, target: &str) {
         .output()
     {
         Ok(_) => println!("Created {}", svg_target),
-        Err(e) => eprintln!(
-            "Error running dot on {}: {}, image may not be created",
-            dot_target, e
-        ),
+        Err(e) => {
+            eprintln!(
+                "Error running dot on {}: {}, drawing it without",
+                dot_target, e
+            );
+            match File::create(&svg_target).and_then(|mut f| f.write_all(to_svg(node).as_bytes())) {
+                Ok(_) => println!("Created {}", svg_target),
+                Err(e) => eprintln!("Error writing to {}: {}", svg_target, e),
+            }
+        }
+    }
+}
+
+const SVG_STEP_X: usize = 40;
+const SVG_STEP_Y: usize = 50;
+const SVG_RADIUS: usize = 15;
+
+// a plain drawing for when graphviz isn't there: the leaves side by side in order,
+// each operator centered over its operands, one row per depth
+pub fn to_svg(node: &Node) -> String {
+    // label, x and y in steps, and the index of the parent
+    let mut nodes: Vec<(String, f64, usize, Option<usize>)> = Vec::new();
+    let mut leaves = 0;
+    layout(node, 0, None, &mut leaves, &mut nodes);
+    let depth = nodes.iter().map(|n| n.2).max().unwrap_or(0);
+    let pos = |x: f64, y: usize| {
+        (
+            x * SVG_STEP_X as f64 + SVG_STEP_X as f64 / 2.0,
+            y * SVG_STEP_Y + SVG_STEP_Y / 2,
+        )
+    };
+    let mut svg = String::new();
+    writeln!(
+        svg,
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\">",
+        leaves.max(1) * SVG_STEP_X,
+        (depth + 1) * SVG_STEP_Y
+    )
+    .unwrap();
+    // the edges first, so the circles are drawn over them
+    for (_, x, y, parent) in &nodes {
+        if let Some(parent) = parent {
+            let (x1, y1) = pos(nodes[*parent].1, nodes[*parent].2);
+            let (x2, y2) = pos(*x, *y);
+            writeln!(
+                svg,
+                "\t<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"black\"/>",
+                x1, y1, x2, y2
+            )
+            .unwrap();
+        }
+    }
+    for (label, x, y, _) in &nodes {
+        let (x, y) = pos(*x, *y);
+        writeln!(
+            svg,
+            "\t<circle cx=\"{}\" cy=\"{}\" r=\"{}\" fill=\"white\" stroke=\"black\"/>",
+            x, y, SVG_RADIUS
+        )
+        .unwrap();
+        let label = label.replace('&', "&amp;").replace('>', "&gt;");
+        writeln!(
+            svg,
+            "\t<text x=\"{}\" y=\"{}\" text-anchor=\"middle\" dominant-baseline=\"central\">{}</text>",
+            x, y, label
+        )
+        .unwrap();
     }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+// pushes the node then its operands, returns its x
+fn layout(
+    node: &Node,
+    depth: usize,
+    parent: Option<usize>,
+    leaves: &mut usize,
+    nodes: &mut Vec<(String, f64, usize, Option<usize>)>,
+) -> f64 {
+    let index = nodes.len();
+    let (label, operands) = match node {
+        Const(c) => ((*c as u8).to_string(), vec![]),
+        Var(v) => (v.get().name.to_string(), vec![]),
+        Not(n) => ("!".to_string(), vec![&**n]),
+        Binary { op, left, right } => (op.to_string(), vec![&**left, &**right]),
+    };
+    nodes.push((label, 0.0, depth, parent));
+    let x = if operands.is_empty() {
+        *leaves += 1;
+        (*leaves - 1) as f64
+    } else {
+        let xs: Vec<f64> = operands
+            .into_iter()
+            .map(|operand| layout(operand, depth + 1, Some(index), leaves, nodes))
+            .collect();
+        xs.iter().sum::<f64>() / xs.len() as f64
+    };
+    nodes[index].1 = x;
+    x
+}
+
+// the nodes of each depth share a rank, so every level of the tree lines up
+pub fn to_dot(node: &Node) -> String {
+    let mut dot = String::new();
+    let mut idx = HashMap::new();
+    let mut ranks = Vec::new();
+    dot.push_str("digraph {\n");
+    dot.push_str("\tnode [shape=none];\n");
+    dot.push_str("\tedge [arrowhead=none];\n");
+    dot.push('\n');
+    print_dot_node(&mut dot, node, &mut idx, 0, &mut ranks);
+    dot.push('\n');
+    for rank in ranks {
+        writeln!(dot, "\t{{ rank=same; {}; }}", rank.join("; ")).unwrap();
+    }
+    dot.push('}');
+    dot
 }
 
 fn get_idx(node: &Node, idx: &mut HashMap<char, usize>) -> String {
@@ -90,8 +196,19 @@ fn get_idx(node: &Node, idx: &mut HashMap<char, usize>) -> String {
 
 use std::fmt::Write as _;
 
-fn print_dot_node(dot: &mut String, node: &Node, idx: &mut HashMap<char, usize>) -> String {
+// `ranks` gets the id of every node at the index of its depth
+fn print_dot_node(
+    dot: &mut String,
+    node: &Node,
+    idx: &mut HashMap<char, usize>,
+    depth: usize,
+    ranks: &mut Vec<Vec<String>>,
+) -> String {
     let id = get_idx(node, idx);
+    if ranks.len() == depth {
+        ranks.push(Vec::new());
+    }
+    ranks[depth].push(id.clone());
     match node {
         Const(c) => {
             writeln!(dot, "\t{} [label=\"{}\"];", id, (*c as u8)).unwrap();
@@ -102,14 +219,14 @@ fn print_dot_node(dot: &mut String, node: &Node, idx: &mut HashMap<char, usize>)
         }
         Binary { op, left, right } => {
             writeln!(dot, "\t{} [label=\"{}\"];", id, op).unwrap();
-            let left_id = print_dot_node(dot, left, idx);
+            let left_id = print_dot_node(dot, left, idx, depth + 1, ranks);
             writeln!(dot, "\t{} -> {};", id, left_id).unwrap();
-            let right_id = print_dot_node(dot, right, idx);
+            let right_id = print_dot_node(dot, right, idx, depth + 1, ranks);
             writeln!(dot, "\t{} -> {};", id, right_id).unwrap();
         }
         Not(operand) => {
             writeln!(dot, "\t{} [label=\"!\"];", id).unwrap();
-            let operand_id = print_dot_node(dot, operand, idx);
+            let operand_id = print_dot_node(dot, operand, idx, depth + 1, ranks);
             writeln!(dot, "\t{} -> {};", id, operand_id).unwrap();
         }
     }
@@ -1,18 +1,37 @@
 // an AST to parse logical expressions in rpn
 
+#[allow(dead_code)]
+#[path = "../arg_error.rs"]
+mod arg_error;
+#[allow(dead_code)]
+mod cached_eval;
+#[allow(dead_code)]
+mod cnf_builder;
+#[allow(dead_code)]
+mod dimacs;
 mod dot_graph;
+#[allow(dead_code)]
+#[path = "../ex06/node.rs"]
+mod ex06_node;
 mod expr_generator;
 mod node;
+#[allow(dead_code)]
+mod truth_table;
+#[allow(dead_code)]
+mod workbook;
 
 use crate::node::Tree;
+use arg_error::ArgError;
 use dot_graph::create_graph;
 use expr_generator::random_rpn_expr;
-use node::ParseError;
+use node::{parse_line, ParseError};
 use std::env::args;
+use std::io::stdin;
 
 struct Args {
     expr: String,
     dot: bool,
+    count: bool,
 }
 
 fn sat(formula: &str) -> bool {
@@ -25,61 +44,1789 @@ fn sat(formula: &str) -> bool {
     }
 }
 
-fn parse_args() -> Result<Args, String> {
-    let mut args = args();
+fn print_count(tree: &Tree) {
+    match tree.count_models() {
+        Ok(count) => println!("{}", count),
+        Err(e) => println!("Error: {:?}", e),
+    }
+}
+
+fn parse_args(args: impl Iterator<Item = String>) -> Result<Args, ArgError> {
     let mut expr = String::new();
     let mut dot = false;
-    let path = args.next().unwrap_or_else(|| "ex07".to_string());
-
+    let mut count = false;
     for arg in args {
-        if let Some(arg) = arg.strip_prefix('-') {
+        if let Some(arg) = arg.strip_prefix('-').filter(|flags| !flags.is_empty()) {
             for c in arg.chars() {
                 match c {
                     'd' => dot = true,
+                    'c' => count = true,
                     'r' => {
-                        if expr.is_empty() {
-                            expr = random_rpn_expr(3, 5);
-                        } else {
-                            return Err(path);
+                        if !expr.is_empty() {
+                            return Err(ArgError::ConflictingRandom);
                         }
+                        expr = random_rpn_expr(3, 5);
                     }
-                    _ => return Err(path),
+                    _ => return Err(ArgError::UnknownFlag(c)),
                 }
             }
         } else if expr.is_empty() {
+            // a lone "-" reads the formulas from stdin
             expr = arg;
         } else {
-            return Err(path);
+            return Err(ArgError::TooManyArgs);
         }
     }
     if expr.is_empty() {
-        Err(path)
+        Err(ArgError::MissingFormula)
     } else {
-        Ok(Args { expr, dot })
+        Ok(Args { expr, dot, count })
     }
 }
 
 fn main() -> Result<(), ParseError> {
-    let (expr, dot) = match parse_args() {
-        Ok(args) => (args.expr, args.dot),
-        Err(path) => {
-            println!("Usage: {} <formula | -r> [-d]", path);
+    let mut args = args();
+    let path = args.next().unwrap_or_else(|| "ex07".to_string());
+    let (expr, dot, count) = match parse_args(args) {
+        Ok(args) => (args.expr, args.dot, args.count),
+        Err(e) => {
+            println!("Error: {:?}", e);
+            println!("Usage: {} <formula | - | -r> [-c] [-d]", path);
             println!("formula: a propositional boolean formula in rpn, ex: AB&C|");
             println!("Options:");
             println!("  -r  use a randomly generated formula");
+            println!("  -   read formulas from stdin, one per line");
+            println!("  -c  print the number of models instead of the satisfiability");
             println!("  -d  print the dot graph of the formula and generate an image from it");
             return Ok(());
         }
     };
+    if expr == "-" {
+        // answered line by line, as they come
+        let lines = stdin().lines().map_while(Result::ok);
+        for tree in lines.filter_map(|line| parse_line(&line)) {
+            match tree {
+                Ok(tree) if count => print_count(&tree),
+                Ok(tree) => println!("{}", tree.satisfy()),
+                Err(e) => println!("Error: {:?}", e),
+            }
+        }
+        return Ok(());
+    }
     println!("Input:\n{}", expr);
     if dot {
         create_graph(&expr.parse::<Tree>()?.root, "ex07_in");
     }
-    println!("{}", sat(&expr));
+    if let Ok(tree) = expr.parse::<Tree>() {
+        // the whole formula being constant is the answer, not a warning
+        let root = tree.root.to_string();
+        for subtree in tree.root.constant_subtrees() {
+            if subtree != root {
+                eprintln!("Warning: {} has the same value on every row", subtree);
+            }
+        }
+    }
+    if count {
+        print_count(&expr.parse::<Tree>()?);
+    } else {
+        println!("{}", sat(&expr));
+    }
     Ok(())
 }
 
 #[cfg(test)]
 mod tests {
-    // TODO
+    use super::*;
+    use crate::node::{Node, Variable};
+    use std::collections::HashMap;
+
+    fn get_table(tree: &Tree, vars: &str) -> Vec<bool> {
+        let var_list: Vec<char> = ('A'..='Z').filter(|&c| vars.contains(c)).collect();
+        let mut res = Vec::with_capacity(1 << var_list.len());
+        for i in 0..(1 << var_list.len()) {
+            for (j, &name) in var_list.iter().enumerate() {
+                let j = var_list.len() - j - 1;
+                let value = (i >> j) & 1 == 1;
+                tree.variables[name as usize - 'A' as usize].set(Variable { name, value });
+            }
+            res.push(tree.root.eval());
+        }
+        res
+    }
+
+    #[test]
+    fn ex07_fold_size() {
+        fn count(node: &Node) -> usize {
+            match node {
+                Node::Const(_) | Node::Var(_) => 1,
+                Node::Not(n) => 1 + count(n),
+                Node::Binary { left, right, .. } => 1 + count(left) + count(right),
+            }
+        }
+        for _ in 0..100 {
+            let expr = random_rpn_expr(4, 5);
+            let root = expr.parse::<Tree>().expect("input is valid").root;
+            let size = root.fold(|_| 1, |_| 1, |n| n + 1, |_, l, r| l + r + 1);
+            assert_eq!(size, count(&root), "{}", expr);
+        }
+        // deep enough to overflow a recursive fold
+        let deep = format!("A{}", "!".repeat(100_000));
+        let root = deep.parse::<Tree>().expect("input is valid").root;
+        let size = root.fold(|_| 1, |_| 1, |n| n + 1, |_, l, r| l + r + 1);
+        assert_eq!(size, 100_001);
+    }
+
+    #[test]
+    fn ex07_from_ex06() {
+        for _ in 0..200 {
+            let expr = random_rpn_expr(3, 5);
+            let ex06 = expr.parse::<ex06_node::Tree>().expect("input is valid");
+            // cnf + simplify yields n-ary And/Or children and folded negations
+            let converted = Tree::from(ex06_node::Tree {
+                root: ex06.root.cnf().simplify(),
+                variables: ex06.variables,
+            });
+            let original = expr.parse::<Tree>().expect("input is valid");
+            assert_eq!(
+                get_table(&converted, &expr),
+                get_table(&original, &expr),
+                "{}",
+                expr
+            );
+        }
+    }
+
+    #[test]
+    fn ex07_to_json() {
+        let json = |formula: &str| formula.parse::<Tree>().expect("input is valid").to_json();
+        assert_eq!(
+            json("AB!&"),
+            r#"{"op":"and","left":{"var":"A"},"right":{"not":{"var":"B"}}}"#
+        );
+        assert_eq!(
+            json("1C>"),
+            r#"{"op":"impl","left":{"const":true},"right":{"var":"C"}}"#
+        );
+        assert_eq!(json("0!"), r#"{"not":{"const":false}}"#);
+    }
+
+    #[test]
+    fn ex07_eval_cached() {
+        let vars = ['A', 'B', 'C', 'D', 'E'];
+        for _ in 0..200 {
+            let expr = random_rpn_expr(4, 5);
+            let tree = expr.parse::<Tree>().expect("input is valid");
+            let mut cached = tree.eval_cached();
+            assert_eq!(cached.value(), tree.root.eval(), "{}", expr);
+            // walk every assignment in gray code order, one toggle at a time
+            for i in 1u32..(1 << vars.len()) {
+                let var = vars[i.trailing_zeros() as usize];
+                assert_eq!(cached.toggle(var), tree.root.eval(), "{}", expr);
+            }
+        }
+        // deep enough to overflow a recursive flattening
+        let deep = format!("A{}", "!".repeat(100_001));
+        let tree = deep.parse::<Tree>().expect("input is valid");
+        let mut cached = tree.eval_cached();
+        assert!(cached.value());
+        assert!(!cached.toggle('A'));
+    }
+
+    #[test]
+    fn ex07_dimacs() {
+        use crate::dimacs::{from_dimacs, satisfy_dimacs_file, DimacsError::*};
+
+        let fixture =
+            |name: &str| format!("{}/tests/fixtures/{}", env!("CARGO_MANIFEST_DIR"), name);
+        assert!(satisfy_dimacs_file(&fixture("sat.cnf")).unwrap());
+        assert!(!satisfy_dimacs_file(&fixture("unsat.cnf")).unwrap());
+        assert!(satisfy_dimacs_file(&fixture("missing.cnf")).is_err());
+
+        let (tree, removed) = from_dimacs("p cnf 3 2\n1 -2 0\n3\n0\n", false).unwrap();
+        assert_eq!(tree.root.to_string(), "AB!|C&");
+        assert_eq!(removed, 0);
+        let (tree, _) = from_dimacs("p cnf 0 0\n", false).unwrap();
+        assert_eq!(tree.root.to_string(), "1");
+        assert_eq!(
+            from_dimacs("1 27 0", false).err(),
+            Some(VariableOutOfRange(27))
+        );
+        // the smallest i64 has no i64 absolute value
+        assert_eq!(
+            from_dimacs("-9223372036854775808 0", false).err(),
+            Some(VariableOutOfRange(1 << 63))
+        );
+        assert_eq!(
+            from_dimacs("1 x 0", false).err(),
+            Some(InvalidLiteral("x".to_string()))
+        );
+        assert!(matches!(
+            from_dimacs("p dnf 1 1\n1 0", false).err(),
+            Some(InvalidHeader(_))
+        ));
+    }
+
+    #[test]
+    fn ex07_dimacs_clean() {
+        use crate::dimacs::from_dimacs;
+
+        // the second clause repeats the first in another order, the third is a tautology
+        let input = "p cnf 3 4\n1 -2 0\n-2 1 0\n3 -3 2 0\n3 0\n";
+        let (tree, removed) = from_dimacs(input, true).unwrap();
+        assert_eq!(tree.root.to_string(), "AB!|C&");
+        assert_eq!(removed, 2);
+        let (raw, removed) = from_dimacs(input, false).unwrap();
+        assert_eq!(removed, 0);
+        assert_eq!(raw.root.to_string(), "AB!|B!A|&CC!|B|&C&");
+        assert!(raw.logically_eq(&tree));
+        // a literal repeated in a clause is neither a duplicate nor a tautology
+        assert_eq!(from_dimacs("1 1 0\n", true).unwrap().1, 0);
+    }
+
+    #[test]
+    fn ex07_variables_first_seen() {
+        let vars = |formula: &str| {
+            formula
+                .parse::<Tree>()
+                .expect("input is valid")
+                .variables_first_seen()
+        };
+        assert_eq!(vars("BA&"), ['B', 'A']);
+        assert_eq!(vars("CA&B|C^A!>"), ['C', 'A', 'B']);
+        assert_eq!(vars("10&"), []);
+    }
+
+    #[test]
+    fn ex07_simplify_with() {
+        use crate::node::SimplifyRules;
+
+        let simplify = |formula: &str, rules| {
+            formula
+                .parse::<Tree>()
+                .expect("input is valid")
+                .root
+                .simplify_with(rules)
+                .to_string()
+        };
+        let conservative = SimplifyRules::DOUBLE_NEGATION | SimplifyRules::EQUAL_OPERANDS;
+        assert_eq!(simplify("A1&", conservative), "A1&");
+        assert_eq!(simplify("A1&", SimplifyRules::CONSTANTS), "A");
+        assert_eq!(simplify("A!!1&", SimplifyRules::CONSTANTS), "A!!");
+        assert_eq!(simplify("A!!1&", conservative), "A1&");
+        assert_eq!(simplify("AA&", SimplifyRules::CONSTANTS), "AA&");
+        assert_eq!(simplify("AA&", conservative), "A");
+        assert_eq!(simplify("A!!1&AA^|", SimplifyRules::empty()), "A!!1&AA^|");
+        assert_eq!(simplify("A!!1&AA^|", SimplifyRules::all()), "A");
+        for _ in 0..200 {
+            let expr = random_rpn_expr(4, 4);
+            let tree = expr.parse::<Tree>().expect("input is valid");
+            for rules in [conservative, SimplifyRules::all()] {
+                let simp = tree.root.clone().simplify_with(rules);
+                let simp = Tree::from_root(*simp, tree.variables.clone());
+                assert_eq!(get_table(&simp, &expr), get_table(&tree, &expr), "{}", expr);
+            }
+        }
+    }
+
+    #[test]
+    fn ex07_core_model_count() {
+        let tree = "AB&A|".parse::<Tree>().expect("input is valid");
+        assert_eq!(tree.essential_variables().unwrap(), ['A']);
+        assert_eq!(tree.count_models().unwrap(), 2);
+        assert_eq!(tree.core_model_count().unwrap(), 1);
+
+        let tree = "AB|CC!&|".parse::<Tree>().expect("input is valid");
+        assert_eq!(tree.essential_variables().unwrap(), ['A', 'B']);
+        assert_eq!(tree.count_models().unwrap(), 6);
+        assert_eq!(tree.core_model_count().unwrap(), 3);
+
+        let tree = "AA!|".parse::<Tree>().expect("input is valid");
+        assert!(tree.essential_variables().unwrap().is_empty());
+        assert_eq!(tree.core_model_count().unwrap(), 1);
+    }
+
+    #[test]
+    fn ex07_eval_all() {
+        let all = "AB&"
+            .parse::<Tree>()
+            .expect("input is valid")
+            .eval_all()
+            .unwrap();
+        assert_eq!(all.len(), 4);
+        assert!(!all[&vec![false, false]]);
+        assert!(!all[&vec![false, true]]);
+        assert!(!all[&vec![true, false]]);
+        assert!(all[&vec![true, true]]);
+
+        let all = "BA>"
+            .parse::<Tree>()
+            .expect("input is valid")
+            .eval_all()
+            .unwrap();
+        // keys are in alphabetical order, A first
+        assert!(!all[&vec![false, true]]);
+        assert!(all[&vec![true, false]]);
+    }
+
+    #[test]
+    fn ex07_formula_from_table() {
+        use crate::node::{formula_from_table, SynthError::*};
+
+        let and = "AB&".parse::<Tree>().expect("input is valid");
+        let synth = formula_from_table(&and.truth_table().unwrap(), &['A', 'B']).unwrap();
+        assert_eq!(get_table(&synth, "AB"), get_table(&and, "AB"));
+        for _ in 0..100 {
+            let expr = random_rpn_expr(3, 4);
+            let tree = expr.parse::<Tree>().expect("input is valid");
+            let vars = tree.variables_first_seen();
+            let mut sorted = vars.clone();
+            sorted.sort_unstable();
+            let synth = formula_from_table(&tree.truth_table().unwrap(), &sorted).unwrap();
+            assert_eq!(
+                get_table(&synth, &expr),
+                get_table(&tree, &expr),
+                "{}",
+                expr
+            );
+        }
+        assert_eq!(
+            formula_from_table(&[true, false], &[]).err(),
+            Some(TableLength {
+                expected: 1,
+                found: 2
+            })
+        );
+        let vars: Vec<char> = ('A'..='U').collect();
+        assert_eq!(
+            formula_from_table(&[true, false], &vars).err(),
+            Some(TooManyVariables { found: 21, max: 20 })
+        );
+        assert_eq!(
+            formula_from_table(&[true, false], &['a']).err(),
+            Some(InvalidVariable('a'))
+        );
+        assert_eq!(
+            formula_from_table(&[true; 4], &['A', 'A']).err(),
+            Some(DuplicateVariable('A'))
+        );
+        assert_eq!(
+            formula_from_table(&[true], &[]).unwrap().root.to_string(),
+            "1"
+        );
+        assert_eq!(
+            formula_from_table(&[false; 2], &['C'])
+                .unwrap()
+                .root
+                .to_string(),
+            "0"
+        );
+        let synth = |expr: &str, vars: &[char]| {
+            let table = expr
+                .parse::<Tree>()
+                .expect("input is valid")
+                .truth_table()
+                .unwrap();
+            let synth = formula_from_table(&table, vars).unwrap();
+            synth.root.to_string()
+        };
+        assert_eq!(synth("AB&", &['A', 'B']), "AB&");
+        assert_eq!(synth("AB>", &['A', 'B']), "A!B|");
+        // the majority, one product per pair instead of the four minterms
+        assert_eq!(synth("AB&AC&|BC&|", &['A', 'B', 'C']), "AB&AC&|BC&|");
+        // A&B | A&!B merges into A, the unused B goes away
+        assert_eq!(synth("AB&AB!&|", &['A', 'B']), "A");
+        // no two rows of a parity merge, it stays the sum of its minterms
+        assert_eq!(
+            synth("AB^C^", &['A', 'B', 'C']),
+            "AB&C&AB!&C!&|A!B&C!&|A!B!&C&|"
+        );
+    }
+
+    #[test]
+    fn ex07_implicant_cover() {
+        let cover = |expr: &str| {
+            let tree = expr.parse::<Tree>().expect("input is valid");
+            tree.implicant_cover().unwrap()
+        };
+        assert_eq!(cover("AB|"), ["A", "B"]);
+        assert_eq!(cover("AB&AC&|BC&|"), ["AB&", "AC&", "BC&"]);
+        assert_eq!(cover("AB>"), ["A!", "B"]);
+        assert_eq!(cover("AA!&"), Vec::<String>::new());
+        assert_eq!(cover("AA!|"), ["1"]);
+        for _ in 0..100 {
+            let expr = random_rpn_expr(3, 4);
+            let tree = expr.parse::<Tree>().expect("input is valid");
+            let terms = tree.implicant_cover().unwrap();
+            // the terms one after the other then the ors between them, in rpn
+            let dnf = match terms.len() {
+                0 => "0".to_string(),
+                n => terms.concat() + &"|".repeat(n - 1),
+            };
+            let dnf = dnf.parse::<Tree>().expect("the cover is valid");
+            assert!(dnf.logically_eq(&tree), "{}", expr);
+        }
+    }
+
+    #[test]
+    fn ex07_prime_implicant_count() {
+        let count = |expr: &str| {
+            let tree = expr.parse::<Tree>().expect("input is valid");
+            tree.prime_implicant_count().unwrap()
+        };
+        assert_eq!(count("AB&AC&|BC&|"), 3);
+        assert_eq!(count("AB|"), 1);
+        // no two false rows of a parity merge
+        assert_eq!(count("AB^C^"), 4);
+        assert_eq!(count("AA!|"), 0);
+        assert_eq!(count("AA!&"), 1);
+    }
+
+    #[test]
+    fn ex07_prime_clauses() {
+        let clauses = |expr: &str| {
+            let tree = expr.parse::<Tree>().expect("input is valid");
+            let mut clauses: Vec<_> = tree.prime_clauses().unwrap().collect();
+            assert_eq!(clauses.len(), tree.prime_implicant_count().unwrap());
+            clauses.sort_unstable();
+            clauses
+        };
+        assert_eq!(
+            clauses("AB&AC&|BC&|"),
+            [
+                [('A', true), ('B', true)],
+                [('A', true), ('C', true)],
+                [('B', true), ('C', true)]
+            ]
+        );
+        assert_eq!(clauses("AB>"), [[('A', false), ('B', true)]]);
+        assert_eq!(clauses("AA!&"), [Vec::new()]);
+        assert!(clauses("AA!|").is_empty());
+        // the minimized cnf only picks among them
+        for _ in 0..100 {
+            let expr = random_rpn_expr(3, 4);
+            let tree = expr.parse::<Tree>().expect("input is valid");
+            let (_, cnf) = tree.minimize_both().unwrap();
+            let cnf = cnf.cnf_clauses();
+            for clause in &cnf {
+                let mut clause = clause.clone();
+                clause.sort_unstable();
+                assert!(clauses(&expr).contains(&clause), "{}", expr);
+            }
+        }
+    }
+
+    #[test]
+    fn ex07_minimize_both() {
+        let both = |expr: &str| {
+            let tree = expr.parse::<Tree>().expect("input is valid");
+            let (dnf, cnf) = tree.minimize_both().unwrap();
+            assert!(dnf.logically_eq(&tree), "{}", expr);
+            assert!(cnf.logically_eq(&tree), "{}", expr);
+            (dnf, cnf)
+        };
+        let (dnf, cnf) = both("AB&AC&|BC&|");
+        assert_eq!(dnf.root.to_string(), "AB&AC&|BC&|");
+        assert_eq!(cnf.root.to_string(), "AB|AC|&BC|&");
+        assert_eq!(cnf.cnf_stats(), (3, 6));
+        // distributing the dnf back into a cnf doesn't find the small one
+        assert_eq!(dnf.cnf_stats(), (8, 24));
+        let (dnf, cnf) = both("AB>");
+        assert_eq!(dnf.root.to_string(), "A!B|");
+        assert_eq!(cnf.root.to_string(), "A!B|");
+        let (dnf, cnf) = both("AB|C&");
+        assert_eq!(dnf.root.to_string(), "AC&BC&|");
+        assert_eq!(cnf.root.to_string(), "AB|C&");
+        assert_eq!(cnf.cnf_stats(), (2, 3));
+        let (dnf, cnf) = both("AA!&");
+        assert_eq!(
+            (dnf.root.to_string(), cnf.root.to_string()),
+            ("0".into(), "0".into())
+        );
+        let (dnf, cnf) = both("AA!|");
+        assert_eq!(
+            (dnf.root.to_string(), cnf.root.to_string()),
+            ("1".into(), "1".into())
+        );
+        for _ in 0..100 {
+            let expr = random_rpn_expr(3, 4);
+            let tree = expr.parse::<Tree>().expect("input is valid");
+            let (_, cnf) = both(&expr);
+            let canonical = tree.canonical_cnf().unwrap();
+            assert!(cnf.cnf_stats().1 <= canonical.cnf_stats().1, "{}", expr);
+        }
+    }
+
+    #[test]
+    fn ex07_logically_eq() {
+        let eq = |a: &str, b: &str| {
+            let a = a.parse::<Tree>().expect("input is valid");
+            let b = b.parse::<Tree>().expect("input is valid");
+            a.logically_eq(&b)
+        };
+        assert!(eq("AB>", "A!B|"));
+        assert!(eq("AB^", "AB|AB&!&"));
+        assert!(!eq("AB>", "BA>"));
+        // the variables don't have to match
+        assert!(eq("A", "ABB!|&"));
+        assert!(eq("AA!|", "1"));
+        assert!(!eq("A", "B"));
+    }
+
+    #[test]
+    fn ex07_canonical_cnf() {
+        let tree = "AB^C|".parse::<Tree>().expect("input is valid");
+        // false on ABC = 000 and 110
+        assert_eq!(
+            tree.canonical_cnf().unwrap().root.to_string(),
+            "AB|C|A!B!|C|&"
+        );
+        assert_eq!(
+            "A!A|"
+                .parse::<Tree>()
+                .unwrap()
+                .canonical_cnf()
+                .unwrap()
+                .root
+                .to_string(),
+            "1"
+        );
+        for _ in 0..200 {
+            let expr = random_rpn_expr(3, 4);
+            let tree = expr.parse::<Tree>().expect("input is valid");
+            let canonical = tree.canonical_cnf().unwrap();
+            assert!(canonical.logically_eq(&tree), "{}", expr);
+            let cnf = Tree::from_root(*tree.root.cnf(), tree.variables.clone());
+            assert!(canonical.logically_eq(&cnf), "{}", expr);
+        }
+    }
+
+    #[test]
+    fn ex07_boolean_derivative() {
+        let derivative = |expr: &str, var: char| {
+            let tree = expr.parse::<Tree>().expect("input is valid");
+            let root = tree.root.boolean_derivative(var);
+            Tree::from_root(root, tree.variables.clone())
+        };
+        let b = "B".parse::<Tree>().unwrap();
+        assert!(derivative("AB&", 'A').logically_eq(&b));
+        assert!(derivative("AB|", 'A').logically_eq(&"B!".parse().unwrap()));
+        // flipping A always flips a xor, and never changes what doesn't depend on it
+        assert!(derivative("AB^", 'A').logically_eq(&"1".parse().unwrap()));
+        assert!(derivative("BC&", 'A').logically_eq(&"0".parse().unwrap()));
+        assert_eq!(derivative("AB&", 'A').root.to_string(), "1B&0B&^");
+    }
+
+    #[test]
+    fn ex07_unate() {
+        let and = "AB&".parse::<Tree>().expect("input is valid");
+        assert!(and.is_positive_unate('A') && and.is_positive_unate('B'));
+        assert!(!and.is_negative_unate('A') && !and.is_negative_unate('B'));
+        let tree = "A!B&".parse::<Tree>().expect("input is valid");
+        assert!(tree.is_negative_unate('A') && !tree.is_positive_unate('A'));
+        assert!(tree.is_positive_unate('B'));
+        // xor is neither, and a missing variable is both
+        let xor = "AB^".parse::<Tree>().expect("input is valid");
+        assert!(!xor.is_positive_unate('A') && !xor.is_negative_unate('A'));
+        assert!(xor.is_positive_unate('C') && xor.is_negative_unate('C'));
+    }
+
+    #[test]
+    fn ex07_parse_args() {
+        let parse = |args: &[&str]| parse_args(args.iter().map(|s| s.to_string())).err();
+        arg_error::check_common_flags(parse);
+        assert_eq!(parse(&["AB&", "-cd"]), None);
+        assert_eq!(parse(&["AB&", "BA&"]), Some(ArgError::TooManyArgs));
+        assert_eq!(parse(&["-", "-d"]), None);
+        assert_eq!(parse(&["-", "AB&"]), Some(ArgError::TooManyArgs));
+        assert_eq!(parse(&["-", "-r"]), Some(ArgError::ConflictingRandom));
+    }
+
+    #[test]
+    fn ex07_restrict() {
+        let tree = "AB|".parse::<Tree>().expect("input is valid");
+        let partial = HashMap::from([('A', true)]);
+        assert!(matches!(tree.root.restrict(&partial), Node::Const(true)));
+        let partial = HashMap::from([('A', false)]);
+        assert_eq!(tree.root.restrict(&partial).to_string(), "B");
+        let tree = "AB&C|".parse::<Tree>().expect("input is valid");
+        let partial = HashMap::from([('A', true), ('C', false)]);
+        assert_eq!(tree.root.restrict(&partial).to_string(), "B");
+        assert_eq!(tree.root.restrict(&HashMap::new()).to_string(), "AB&C|");
+    }
+
+    #[test]
+    fn ex07_sum_product() {
+        let tree = "A".parse::<Tree>().expect("input is valid");
+        let var = |c: char| Box::new(Node::Var(tree.variables[c as usize - 'A' as usize].clone()));
+        let clauses = vec![
+            var('A') | var('B'),
+            !var('A') | var('C'),
+            var('B') | !var('C'),
+        ];
+        let cnf: Box<Node> = clauses.into_iter().product();
+        assert_eq!(cnf.to_string(), "AB|A!C|&BC!|&");
+        let any: Box<Node> = "ABC".chars().map(var).sum();
+        assert_eq!(any.to_string(), "AB|C|");
+        let empty: Box<Node> = std::iter::empty().sum();
+        assert!(matches!(*empty, Node::Const(false)));
+        let empty: Box<Node> = std::iter::empty().product();
+        assert!(matches!(*empty, Node::Const(true)));
+    }
+
+    #[test]
+    fn ex07_eval_checked() {
+        use crate::node::DepthError;
+
+        let tree = "AB&!".parse::<Tree>().expect("input is valid");
+        assert_eq!(tree.root.depth(), 3);
+        assert_eq!(tree.eval_checked(3), Ok(true));
+        assert_eq!(
+            tree.eval_checked(2),
+            Err(DepthError::TooDeep {
+                depth: 3,
+                max_depth: 2
+            })
+        );
+        let deep = format!("A{}", "!".repeat(100_000));
+        let tree = deep.parse::<Tree>().expect("input is valid");
+        assert_eq!(
+            tree.eval_checked(1000),
+            Err(DepthError::TooDeep {
+                depth: 100_001,
+                max_depth: 1000
+            })
+        );
+    }
+
+    #[test]
+    fn ex07_deep_formula() {
+        // A B& B& ... nests 50k ands to the left
+        let deep = format!("A{}", "B&".repeat(50_000));
+        let tree = deep.parse::<Tree>().expect("input is valid");
+        assert_eq!(tree.root.to_string(), deep);
+        assert!(!tree.root.eval());
+        tree.variables[0].set(Variable {
+            name: 'A',
+            value: true,
+        });
+        tree.variables[1].set(Variable {
+            name: 'B',
+            value: true,
+        });
+        assert!(tree.root.eval());
+        let deep = format!("A{}", "!".repeat(50_001));
+        let tree = deep.parse::<Tree>().expect("input is valid");
+        assert_eq!(tree.root.to_string(), deep);
+        assert!(tree.root.eval());
+    }
+
+    #[test]
+    fn ex07_satisfiable_valid() {
+        let parse = |expr: &str| expr.parse::<Tree>().expect("input is valid");
+        assert!(parse("AA!|").is_valid());
+        assert!(!parse("AA!&").is_satisfiable());
+        assert!(parse("AB&").is_satisfiable() && !parse("AB&").is_valid());
+        for _ in 0..500 {
+            let expr = random_rpn_expr(3, 4);
+            let negated = format!("{}!", expr);
+            assert_eq!(
+                parse(&expr).is_valid(),
+                !parse(&negated).is_satisfiable(),
+                "{}",
+                expr
+            );
+            assert_eq!(
+                parse(&expr).is_satisfiable(),
+                !parse(&negated).is_valid(),
+                "{}",
+                expr
+            );
+        }
+    }
+
+    #[test]
+    fn ex07_is_horn() {
+        let parse = |expr: &str| expr.parse::<Tree>().expect("input is valid");
+        assert_eq!(
+            parse("AB|C!&").cnf_clauses(),
+            [vec![('A', true), ('B', true)], vec![('C', false)]]
+        );
+        assert!(parse("1").cnf_clauses().is_empty());
+        assert_eq!(parse("0").cnf_clauses(), [vec![]]);
+        assert!(parse("A!B!|C|").is_horn());
+        assert!(parse("AB>C&").is_horn());
+        assert!(!parse("AB|").is_horn());
+        assert!(!parse("A!B|C|D!&").is_horn());
+    }
+
+    #[test]
+    fn ex07_satisfy_horn() {
+        let parse = |expr: &str| expr.parse::<Tree>().expect("input is valid");
+        // A, A > B, B & C > D: A and B are forced, C and D aren't
+        let tree = parse("AAB>&BC&D>&");
+        assert_eq!(
+            tree.satisfy_horn(),
+            Some(vec![('A', true), ('B', true), ('C', false), ('D', false)])
+        );
+        // A, A > B, !B
+        assert_eq!(parse("AAB>&B!&").satisfy_horn(), None);
+        // not horn, found by the fallback
+        assert_eq!(
+            parse("AB|A!&").satisfy_horn(),
+            Some(vec![('A', false), ('B', true)])
+        );
+        for _ in 0..200 {
+            let expr = random_rpn_expr(3, 4);
+            let tree = parse(&expr);
+            let model = tree.satisfy_horn();
+            assert_eq!(model.is_some(), tree.satisfy(), "{}", expr);
+            if let Some(model) = model {
+                for (name, value) in model {
+                    tree.variables[name as usize - 'A' as usize].set(Variable { name, value });
+                }
+                assert!(tree.root.eval(), "{}", expr);
+            }
+        }
+    }
+
+    #[test]
+    fn ex07_dual() {
+        let dual = |expr: &str| {
+            let tree = expr.parse::<Tree>().expect("input is valid");
+            tree.root.dual().to_string()
+        };
+        assert_eq!(dual("AB&"), "AB|");
+        assert_eq!(dual("AB|C!&1|"), "AB&C!|0&");
+        assert_eq!(dual("AB>"), "A!B&");
+        for _ in 0..200 {
+            let expr = random_rpn_expr(3, 4);
+            let tree = expr.parse::<Tree>().expect("input is valid");
+            let twice = tree.root.dual().dual();
+            assert!(
+                Tree::from_root(twice, tree.variables.clone()).logically_eq(&tree),
+                "{}",
+                expr
+            );
+            // the dual of f is !f with every variable negated
+            let dual = Tree::from_root(tree.root.dual(), tree.variables.clone());
+            let flipped: String = expr
+                .chars()
+                .flat_map(|c| {
+                    if c.is_ascii_uppercase() {
+                        vec![c, '!']
+                    } else {
+                        vec![c]
+                    }
+                })
+                .collect();
+            assert!(
+                dual.logically_eq(&format!("{}!", flipped).parse().unwrap()),
+                "{}",
+                expr
+            );
+        }
+    }
+
+    #[test]
+    fn ex07_write_dimacs() {
+        let tree = "AB|C!&".parse::<Tree>().expect("input is valid");
+        let dimacs = tree.to_dimacs();
+        assert_eq!(dimacs, "p cnf 3 2\n1 2 0\n-3 0\n");
+        let mut out = Vec::new();
+        tree.write_dimacs(&mut out).unwrap();
+        assert_eq!(out, dimacs.as_bytes());
+        assert_eq!("1".parse::<Tree>().unwrap().to_dimacs(), "p cnf 0 0\n");
+        assert_eq!("0".parse::<Tree>().unwrap().to_dimacs(), "p cnf 0 1\n0\n");
+        for _ in 0..100 {
+            let expr = random_rpn_expr(3, 4);
+            let tree = expr.parse::<Tree>().expect("input is valid");
+            let (back, _) = dimacs::from_dimacs(&tree.to_dimacs(), false).unwrap();
+            assert!(back.logically_eq(&tree), "{}", expr);
+        }
+    }
+
+    #[test]
+    fn ex07_truth_table_diff() {
+        use crate::node::truth_table_diff;
+
+        assert_eq!(
+            truth_table_diff("AB&", "AB|"),
+            Ok(vec![
+                (vec![false, true], false, true),
+                (vec![true, false], false, true)
+            ])
+        );
+        assert_eq!(truth_table_diff("AB>", "A!B|"), Ok(vec![]));
+        assert_eq!(
+            truth_table_diff("A", "B"),
+            Ok(vec![
+                (vec![false, true], false, true),
+                (vec![true, false], true, false)
+            ])
+        );
+        assert_eq!(truth_table_diff("A&", "A"), Err(ParseError::MissingOperand));
+    }
+
+    #[test]
+    fn ex07_assignment() {
+        use crate::node::{Assignment, AssignmentError};
+
+        let vars = ['A', 'C', 'B'];
+        let assignment = Assignment::try_from((&vars[..], vec![true, false, true])).unwrap();
+        assert!(assignment['A'] && !assignment['C'] && assignment['B']);
+        assert_eq!(assignment.get('C'), Some(false));
+        assert_eq!(assignment.get('D'), None);
+        assert_eq!(
+            assignment.iter().map(|&(v, _)| v).collect::<String>(),
+            "ACB"
+        );
+        assert_eq!(
+            Assignment::try_from((&vars[..], vec![true])),
+            Err(AssignmentError::LengthMismatch { vars: 3, values: 1 })
+        );
+        assert_eq!(
+            Assignment::try_from((&['A', 'A'][..], vec![true, false])),
+            Err(AssignmentError::DuplicateVariable('A'))
+        );
+    }
+
+    #[test]
+    fn ex07_literals() {
+        let literals = |expr: &str| {
+            expr.parse::<Tree>()
+                .expect("input is valid")
+                .root
+                .literals()
+        };
+        assert_eq!(literals("A!B|A&"), [('A', false), ('B', true), ('A', true)]);
+        assert_eq!(
+            literals("AB&!!C!|"),
+            [('A', true), ('B', true), ('C', false)]
+        );
+        assert_eq!(literals("A!B>!"), [('A', true), ('B', false)]);
+        assert!(literals("10&").is_empty());
+    }
+
+    #[test]
+    fn ex07_satisfy_dpll() {
+        let parse = |expr: &str| expr.parse::<Tree>().expect("input is valid");
+        // B only appears positive, then A only negated in what's left, C isn't needed
+        assert_eq!(
+            parse("AB|A!C|&").satisfy_dpll(),
+            Some(vec![('A', false), ('B', true), ('C', false)])
+        );
+        // the unit A satisfies the first clause, then forces B, D is left unassigned
+        assert_eq!(
+            parse("AD!|A!B|&A&").satisfy_dpll(),
+            Some(vec![('A', true), ('B', true), ('D', false)])
+        );
+        assert_eq!(parse("AA!&").satisfy_dpll(), None);
+        assert_eq!(parse("AB^AB=&").satisfy_dpll(), None);
+        for _ in 0..500 {
+            let expr = random_rpn_expr(4, 4);
+            let tree = parse(&expr);
+            let model = tree.satisfy_dpll();
+            assert_eq!(model.is_some(), tree.satisfy(), "{}", expr);
+            if let Some(model) = model {
+                for (name, value) in model {
+                    tree.variables[name as usize - 'A' as usize].set(Variable { name, value });
+                }
+                assert!(tree.root.eval(), "{}", expr);
+            }
+        }
+    }
+
+    #[test]
+    fn ex07_cnf_stats() {
+        let tree = "AB|C&".parse::<Tree>().expect("input is valid");
+        assert_eq!(tree.cnf_stats(), (2, 3));
+        // one clause of 3 literals for each of the 5 false rows
+        assert_eq!(tree.canonical_cnf().unwrap().cnf_stats(), (5, 15));
+        assert_eq!("1".parse::<Tree>().unwrap().cnf_stats(), (0, 0));
+        assert_eq!("0".parse::<Tree>().unwrap().cnf_stats(), (1, 0));
+    }
+
+    #[test]
+    fn ex07_simplify_implication_chains() {
+        let simplify = |expr: &str| {
+            let tree = expr.parse::<Tree>().expect("input is valid");
+            let simp = tree.root.clone().simplify();
+            let simp = Tree::from_root(*simp, tree.variables.clone());
+            assert!(simp.logically_eq(&tree), "{}", expr);
+            simp.root.to_string()
+        };
+        assert_eq!(simplify("AAB>>"), "AB>");
+        assert_eq!(simplify("AAAB>>>"), "AB>");
+        assert_eq!(simplify("ABA>>"), "1");
+        assert_eq!(simplify("AB&AB&C>>"), "AB&C>");
+        // the antecedent has to repeat
+        assert_eq!(simplify("ABC>>"), "ABC>>");
+    }
+
+    #[test]
+    fn ex07_workbook() {
+        use crate::workbook::Workbook;
+
+        let mut book = Workbook::new();
+        assert_eq!(book.add("AB&"), Ok(0));
+        assert_eq!(book.add("BC|!"), Ok(1));
+        assert_eq!(book.add("A&"), Err(ParseError::MissingOperand));
+        assert_eq!(book.formulas().len(), 2);
+        let assignment = HashMap::from([('A', true), ('B', true)]);
+        assert_eq!(book.eval_all(&assignment), [true, false]);
+        // B is shared: turning it off changes both
+        let assignment = HashMap::from([('A', true), ('B', false)]);
+        assert_eq!(book.eval_all(&assignment), [false, true]);
+        assert_eq!(book.eval_all(&HashMap::new()), [false, true]);
+    }
+
+    #[test]
+    fn ex07_postfix_tokens() {
+        use crate::node::{from_postfix_tokens, BinOp, Token};
+
+        let tree = "AB&1!|".parse::<Tree>().expect("input is valid");
+        let tokens = tree.root.to_postfix_tokens();
+        assert_eq!(
+            tokens,
+            [
+                Token::Var('A'),
+                Token::Var('B'),
+                Token::Op(BinOp::And),
+                Token::Const(true),
+                Token::Not,
+                Token::Op(BinOp::Or)
+            ]
+        );
+        assert_eq!(from_postfix_tokens(&tokens).unwrap().to_string(), "AB&1!|");
+        for _ in 0..100 {
+            let expr = random_rpn_expr(3, 5);
+            let tree = expr.parse::<Tree>().expect("input is valid");
+            let back = from_postfix_tokens(&tree.root.to_postfix_tokens()).unwrap();
+            assert_eq!(back.to_string(), expr);
+        }
+        let err = |tokens: &[Token]| from_postfix_tokens(tokens).err();
+        assert_eq!(err(&[Token::Not]), Some(ParseError::MissingOperand));
+        assert_eq!(
+            err(&[Token::Var('a')]),
+            Some(ParseError::InvalidCharacter('a'))
+        );
+        assert_eq!(
+            err(&[Token::Var('A'), Token::Var('B')]),
+            Some(ParseError::UnbalancedExpression)
+        );
+        assert_eq!(err(&[]), Some(ParseError::UnbalancedExpression));
+    }
+
+    #[test]
+    fn ex07_walsh_spectrum() {
+        let spectrum = |expr: &str| {
+            expr.parse::<Tree>()
+                .expect("input is valid")
+                .walsh_spectrum()
+                .unwrap()
+        };
+        assert_eq!(spectrum("AB&"), [2, 2, 2, -2]);
+        // a linear function correlates with itself only
+        assert_eq!(spectrum("AB^"), [0, 0, 0, 4]);
+        assert_eq!(spectrum("A"), [0, 2]);
+        assert_eq!(spectrum("0"), [1]);
+        // Parseval: the squares always sum to 4^n
+        for _ in 0..50 {
+            let expr = random_rpn_expr(4, 4);
+            let tree = expr.parse::<Tree>().expect("input is valid");
+            let n = tree.truth_table().unwrap().len() as i32;
+            let sum: i32 = tree.walsh_spectrum().unwrap().iter().map(|w| w * w).sum();
+            assert_eq!(sum, n * n, "{}", expr);
+        }
+    }
+
+    #[test]
+    fn ex07_negate() {
+        let mut tree = "AB&".parse::<Tree>().expect("input is valid");
+        tree.root.negate();
+        assert_eq!(tree.root.to_string(), "A!B!|");
+        assert_eq!(tree.truth_table().unwrap(), [true, true, true, false]);
+        tree.root.negate();
+        assert_eq!(tree.root.to_string(), "AB&");
+        for _ in 0..200 {
+            let expr = random_rpn_expr(3, 4);
+            let mut tree = expr.parse::<Tree>().expect("input is valid");
+            let table = tree.truth_table().unwrap();
+            tree.root.negate();
+            let negated: Vec<bool> = table.iter().map(|b| !b).collect();
+            assert_eq!(tree.truth_table().unwrap(), negated, "{}", expr);
+            // only & and | are left, and ! only on variables
+            let rpn = tree.root.to_string();
+            assert!(!rpn.contains(['>', '^', '=']), "{} -> {}", expr, rpn);
+            assert!(!rpn.contains("!!"), "{} -> {}", expr, rpn);
+            assert!(
+                rpn.match_indices('!')
+                    .all(|(i, _)| rpn.as_bytes()[i - 1].is_ascii_uppercase()),
+                "{} -> {}",
+                expr,
+                rpn
+            );
+        }
+        let negate = |expr: &str| {
+            let mut tree = expr.parse::<Tree>().expect("input is valid");
+            tree.root.negate();
+            tree.root.to_string()
+        };
+        // the ! removed, what was under it is still normalized
+        assert_eq!(negate("AB>!"), "A!B|");
+        assert_eq!(negate("AB>"), "AB!&");
+        assert_eq!(negate("AB^"), "AB&A!B!&|");
+        assert_eq!(negate("AB=!"), "AB&A!B!&|");
+    }
+
+    #[test]
+    fn ex07_truth_table_bits() {
+        use crate::truth_table::TruthTable;
+
+        let table =
+            |expr: &str| TruthTable::from_formula(&expr.parse().expect("input is valid")).unwrap();
+        let or = table("AB|");
+        assert_eq!(or.vars(), ['A', 'B']);
+        assert!(!or.get(0) && or.get(1) && or.get(3));
+        assert_eq!(table("AB|") & table("AB^"), table("AB|AB^&"));
+        assert_eq!(table("AB&") | table("AB^"), table("AB|"));
+        assert_eq!(!table("AB|"), table("A!B!&"));
+        assert_eq!(!table("AA!&"), table("AA!|"));
+        // 128 rows, over two words
+        let vars = "ABCDEFG";
+        let and = table("ABCDEFG&&&&&&");
+        let xor = table("ABCDEFG^^^^^^");
+        assert_eq!(and.count_ones(), 1);
+        assert_eq!(xor.count_ones(), 64);
+        assert_eq!(
+            and.clone() | xor.clone(),
+            table(&format!("{}&&&&&&{}^^^^^^|", vars, vars))
+        );
+        assert_eq!(
+            !and.clone() & !xor.clone(),
+            table("ABCDEFG&&&&&&!ABCDEFG^^^^^^!&")
+        );
+        assert_eq!((!xor).count_ones(), 64);
+    }
+
+    #[test]
+    fn ex07_shortest_equivalent() {
+        let shortest = |expr: &str| {
+            let tree = expr.parse::<Tree>().expect("input is valid");
+            let short = tree.shortest_equivalent().unwrap();
+            assert!(short.logically_eq(&tree), "{}", expr);
+            short.root.to_string()
+        };
+        // the cnf of a xor needs two clauses
+        let cnf = "AB^".parse::<Tree>().unwrap().root.cnf().to_string();
+        assert_eq!(cnf, "AB|A!B!|&");
+        assert_eq!(shortest("AB^"), "AB^");
+        assert_eq!(shortest("AB|A!B!|&"), "AB^");
+        assert_eq!(shortest("AB&A|"), "A");
+        assert_eq!(shortest("AA!|"), "1");
+        assert_eq!(shortest("A!B!&!"), "AB|");
+        let four = "AB&CD&|".parse::<Tree>().expect("input is valid");
+        assert!(four.shortest_equivalent().is_none());
+        for _ in 0..20 {
+            let expr = random_rpn_expr(3, 3);
+            assert!(shortest(&expr).len() <= expr.len(), "{}", expr);
+        }
+    }
+
+    #[test]
+    fn ex07_cnf_builder() {
+        use crate::cnf_builder::CnfBuilder;
+
+        let mut builder = CnfBuilder::new();
+        builder.add_clause(&[('A', true), ('B', false)]);
+        builder.add_clause(&[('C', true)]);
+        let tree = builder.build();
+        assert_eq!(tree.root.to_string(), "AB!|C&");
+        assert_eq!(tree.varlist(), ['A', 'B', 'C']);
+        // false only when C is, or when A isn't and B is
+        assert_eq!(
+            tree.truth_table().unwrap(),
+            [false, true, false, false, false, true, false, true]
+        );
+        assert_eq!(CnfBuilder::new().build().root.to_string(), "1");
+        let mut builder = CnfBuilder::new();
+        builder.add_clause(&[]);
+        assert!(!builder.build().satisfy());
+    }
+
+    #[test]
+    fn ex07_clause() {
+        use crate::cnf_builder::{Clause, ClauseError::*, CnfBuilder};
+
+        let clause = |literals: &[(char, bool)]| Clause::try_from(literals);
+        let normal = clause(&[('C', true), ('A', false), ('B', true)]).unwrap();
+        assert_eq!(normal.literals(), [('A', false), ('B', true), ('C', true)]);
+        assert_eq!(
+            clause(&[('B', true), ('A', true), ('B', false)]).err(),
+            Some(Tautology('B'))
+        );
+        let duplicates = clause(&[('B', false), ('A', true), ('B', false), ('A', true)]).unwrap();
+        assert_eq!(duplicates.literals(), [('A', true), ('B', false)]);
+        assert_eq!(clause(&[('a', true)]).err(), Some(InvalidVariable('a')));
+        assert!(clause(&[]).unwrap().literals().is_empty());
+
+        let mut builder = CnfBuilder::new();
+        builder.add(&normal);
+        builder.add(&duplicates);
+        assert_eq!(builder.build().root.to_string(), "A!B|C|AB!|&");
+    }
+
+    #[test]
+    fn ex07_symmetric_in() {
+        let parse = |expr: &str| expr.parse::<Tree>().expect("input is valid");
+        assert!(parse("AB&").symmetric_in('A', 'B'));
+        assert!(!parse("AB>").symmetric_in('A', 'B'));
+        let tree = parse("AB^C&");
+        assert!(tree.symmetric_in('A', 'B') && tree.symmetric_in('B', 'A'));
+        assert!(!tree.symmetric_in('A', 'C'));
+        assert!(tree.symmetric_in('C', 'C'));
+        // neither appears, nothing changes
+        assert!(tree.symmetric_in('X', 'Y'));
+    }
+
+    #[test]
+    fn ex07_constant_subtrees() {
+        let subtrees = |expr: &str| {
+            let tree = expr.parse::<Tree>().expect("input is valid");
+            tree.root.constant_subtrees()
+        };
+        assert_eq!(subtrees("AA^B|"), ["AA^"]);
+        assert_eq!(subtrees("AB|C&"), Vec::<String>::new());
+        assert_eq!(subtrees("AA!|BB!&^C&"), ["AA!|BB!&^"]);
+        assert_eq!(subtrees("AB>AB!&|"), ["AB>AB!&|"]);
+        assert_eq!(subtrees("A1&"), Vec::<String>::new());
+    }
+
+    #[test]
+    fn ex07_random_cnf() {
+        use crate::expr_generator::{random_cnf, random_unsat_cnf};
+        for seed in 0..20 {
+            let cnf = random_cnf(6, 12, 3, seed);
+            assert_eq!(
+                cnf.root.to_string(),
+                random_cnf(6, 12, 3, seed).root.to_string()
+            );
+            assert_eq!(cnf.cnf_stats(), (12, 36));
+            assert_eq!(cnf.satisfy_dpll().is_some(), cnf.satisfy());
+            let unsat = random_unsat_cnf(6, 12, 3, seed);
+            assert_eq!(unsat.cnf_stats(), (20, 60));
+            assert_eq!(unsat.satisfy_dpll(), None);
+            assert!(!unsat.satisfy());
+        }
+    }
+
+    #[test]
+    fn ex07_eval_traced() {
+        let tree = "AB&".parse::<Tree>().expect("input is valid");
+        tree.variables[0].set(Variable {
+            name: 'A',
+            value: true,
+        });
+        let trace = |entries: &[(&str, bool)]| {
+            entries
+                .iter()
+                .map(|&(rpn, value)| (rpn.to_string(), value))
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            tree.eval_traced(),
+            (false, trace(&[("A", true), ("B", false), ("AB&", false)]))
+        );
+        let tree = "AB!|1^".parse::<Tree>().expect("input is valid");
+        let (result, steps) = tree.eval_traced();
+        assert_eq!(result, tree.root.eval());
+        assert_eq!(
+            steps,
+            trace(&[
+                ("A", false),
+                ("B", false),
+                ("B!", true),
+                ("AB!|", true),
+                ("1", true),
+                ("AB!|1^", false),
+            ])
+        );
+    }
+
+    #[test]
+    fn ex07_truth_table_weight() {
+        use crate::node::truth_table_weight;
+        let table = |expr: &str| {
+            expr.parse::<Tree>()
+                .expect("input is valid")
+                .truth_table()
+                .unwrap()
+        };
+        assert_eq!(truth_table_weight(&table("AB|")), 3);
+        assert_eq!(truth_table_weight(&table("AB&C^")), 4);
+        assert_eq!(truth_table_weight(&table("AA!&")), 0);
+        assert_eq!(truth_table_weight(&[]), 0);
+    }
+
+    #[test]
+    fn ex07_canonicalize() {
+        let canonical = |expr: &str| {
+            let tree = expr.parse::<Tree>().expect("input is valid");
+            tree.root.canonicalize().to_string()
+        };
+        assert_eq!(canonical("AB="), canonical("BA="));
+        assert_eq!(canonical("BA="), "AB=");
+        assert_ne!(canonical("AB>"), canonical("BA>"));
+        assert_eq!(canonical("CA&B|"), canonical("BAC&|"));
+        assert_eq!(canonical("BA^!CB>&"), "AB^!CB>&");
+    }
+
+    #[test]
+    fn ex07_implication_graph() {
+        use crate::node::TwoSatError;
+        let graph = |expr: &str| {
+            let tree = expr.parse::<Tree>().expect("input is valid");
+            tree.implication_graph()
+        };
+        assert_eq!(
+            graph("AB|A!C|&"),
+            Ok(vec![
+                (('A', false), ('B', true)),
+                (('B', false), ('A', true)),
+                (('A', true), ('C', true)),
+                (('C', false), ('A', false)),
+            ])
+        );
+        assert_eq!(
+            graph("A!B&"),
+            Ok(vec![
+                (('A', true), ('A', false)),
+                (('B', false), ('B', true))
+            ])
+        );
+        assert_eq!(graph("1"), Ok(vec![]));
+        assert_eq!(graph("ABC||"), Err(TwoSatError::ClauseLength(3)));
+        assert_eq!(graph("0"), Err(TwoSatError::ClauseLength(0)));
+    }
+
+    #[test]
+    fn ex07_satisfy_2sat() {
+        use crate::expr_generator::random_cnf;
+        use crate::node::TwoSatError;
+        let parse = |expr: &str| expr.parse::<Tree>().expect("input is valid");
+        let check_model = |tree: &Tree, model: Vec<(char, bool)>| {
+            for (name, value) in model {
+                tree.variables[name as usize - 'A' as usize].set(Variable { name, value });
+            }
+            tree.root.eval()
+        };
+        // A forces B, B forces C
+        let tree = parse("AA!B|&B!C|&");
+        let model = tree.satisfy_2sat().unwrap();
+        assert_eq!(model, Some(vec![('A', true), ('B', true), ('C', true)]));
+        assert!(check_model(&tree, model.unwrap()));
+        assert_eq!(parse("AB|A!B|&AB!|&A!B!|&").satisfy_2sat(), Ok(None));
+        assert_eq!(parse("AA!&").satisfy_2sat(), Ok(None));
+        assert_eq!(
+            parse("ABC||").satisfy_2sat(),
+            Err(TwoSatError::ClauseLength(3))
+        );
+        for seed in 0..200 {
+            let tree = random_cnf(5, 8, 2, seed);
+            match tree.satisfy_2sat().unwrap() {
+                Some(model) => assert!(check_model(&tree, model), "{}", tree.root),
+                None => assert!(!tree.satisfy(), "{}", tree.root),
+            }
+        }
+    }
+
+    #[test]
+    fn ex07_eval_mask() {
+        for expr in ["AB&", "AB>C^", "DB|A!&", "AB=CD^>E&"] {
+            let tree = expr.parse::<Tree>().expect("input is valid");
+            let rows = 1u64 << tree.varlist().len();
+            let table: Vec<bool> = (0..rows).map(|mask| tree.eval_mask(mask)).collect();
+            assert_eq!(table, get_table(&tree, expr), "{}", expr);
+        }
+    }
+
+    #[test]
+    fn ex07_node_ord() {
+        let parse = |expr: &str| expr.parse::<Tree>().expect("input is valid").root;
+        let sorted = ["0", "1", "A", "B", "A!", "AB&", "BA&C|", "AB>", "BA>"];
+        let mut nodes: Vec<Node> = sorted.iter().rev().map(|expr| parse(expr)).collect();
+        nodes.sort();
+        let strings: Vec<String> = nodes.iter().map(Node::to_string).collect();
+        assert_eq!(strings, sorted);
+        assert!(parse("AB&") == parse("BA&"));
+        assert!(parse("AB>") != parse("BA>"));
+        assert!(parse("CAB|&") == parse("BA|C&"));
+        assert!(parse("AB|") < parse("AB^"));
+    }
+
+    #[test]
+    fn ex07_are_equivalent() {
+        use crate::node::are_equivalent;
+        assert_eq!(are_equivalent("AB&", "BA&"), Ok(true));
+        assert_eq!(are_equivalent("AB>", "BA>"), Ok(false));
+        assert_eq!(are_equivalent("AB>", "A!B|"), Ok(true));
+        assert_eq!(are_equivalent("AB>", "A&"), Err(ParseError::MissingOperand));
+        // 24 variables, the truth table would take 2^24 rows: only the fast path can answer
+        let forward: String = ('A'..='X').collect::<String>() + &"&".repeat(23);
+        let backward: String = "X".to_string()
+            + &('A'..='W')
+                .rev()
+                .map(|v| format!("{}&", v))
+                .collect::<String>();
+        assert_eq!(are_equivalent(&forward, &backward), Ok(true));
+        // deep enough to overflow comparing the trees recursively
+        let deep = format!("A{}{}", "B".repeat(50_000), "&".repeat(50_000));
+        let swapped = format!("{}{}A&", "B".repeat(50_000), "&".repeat(49_999));
+        assert_eq!(are_equivalent(&deep, &swapped), Ok(true));
+        assert_eq!(are_equivalent(&deep, "AB&"), Ok(true));
+        assert_eq!(are_equivalent(&deep, "AB|"), Ok(false));
+    }
+
+    #[test]
+    fn ex07_to_tree_string() {
+        let tree = |expr: &str| {
+            let tree = expr.parse::<Tree>().expect("input is valid");
+            tree.root.to_tree_string()
+        };
+        assert_eq!(tree("AB&C|"), "|\n├── &\n│   ├── A\n│   └── B\n└── C\n");
+        assert_eq!(
+            tree("A!1B>^"),
+            "^\n├── !\n│   └── A\n└── >\n    ├── 1\n    └── B\n"
+        );
+        assert_eq!(tree("A"), "A\n");
+    }
+
+    #[test]
+    fn ex07_cnf_drops_tautologies() {
+        let parse = |expr: &str| expr.parse::<Tree>().expect("input is valid");
+        assert_eq!(parse("AA!|B|C&").cnf_clauses(), [[('C', true)]]);
+        assert!(parse("AA!|BB!|&").cnf_clauses().is_empty());
+        assert_eq!(parse("AB|0&").cnf_clauses(), [vec![]]);
+        assert_eq!(parse("AA!|B|C&").cnf_stats(), (1, 1));
+        assert_eq!(parse("AA!|B|C&").to_dimacs(), "p cnf 3 1\n3 0\n");
+    }
+
+    #[test]
+    fn ex07_from_minterms() {
+        use crate::node::SynthError::*;
+
+        let parse = |expr: &str| expr.parse::<Tree>().expect("input is valid");
+        let and = Tree::from_minterms(&[3], 2).unwrap();
+        assert!(and.logically_eq(&parse("AB&")));
+        assert_eq!(and.root.to_string(), "AB&");
+        let eq = Tree::from_minterms(&[3, 0, 3], 2).unwrap();
+        assert_eq!(eq.root.to_string(), "A!B!&AB&|");
+        assert!(eq.logically_eq(&parse("AB=")));
+        assert_eq!(Tree::from_minterms(&[], 3).unwrap().root.to_string(), "0");
+        let tree = parse("AB>C^");
+        let table = tree.truth_table().unwrap();
+        let minterms: Vec<usize> = (0..table.len()).filter(|&row| table[row]).collect();
+        assert!(Tree::from_minterms(&minterms, 3)
+            .unwrap()
+            .logically_eq(&tree));
+        assert_eq!(
+            Tree::from_minterms(&[4], 2).err(),
+            Some(MintermOutOfRange(4))
+        );
+        assert_eq!(
+            Tree::from_minterms(&[], 27).err(),
+            Some(TooManyVariables { found: 27, max: 26 })
+        );
+    }
+
+    #[test]
+    fn ex07_tree_debug() {
+        let tree = "BA&C!|".parse::<Tree>().expect("input is valid");
+        assert_eq!(
+            format!("{:?}", tree),
+            "Tree { root: BA&C!|, variables: ['A', 'B', 'C'] }"
+        );
+        let tree = "1".parse::<Tree>().expect("input is valid");
+        assert_eq!(format!("{:?}", tree), "Tree { root: 1, variables: [] }");
+    }
+
+    #[test]
+    fn ex07_parse_many() {
+        use crate::node::{parse_line, parse_many};
+
+        let trees = parse_many("AB&\n\n  A&\n\tAB|!  \n");
+        assert_eq!(trees.len(), 3);
+        assert_eq!(trees[0].as_ref().unwrap().root.to_string(), "AB&");
+        assert_eq!(trees[1].as_ref().err(), Some(&ParseError::MissingOperand));
+        assert_eq!(trees[2].as_ref().unwrap().root.to_string(), "AB|!");
+        assert!(parse_many("\n \n").is_empty());
+        assert!(parse_line("  # only a comment").is_none());
+        assert_eq!(
+            parse_line("AB& # and").unwrap().unwrap().root.to_string(),
+            "AB&"
+        );
+    }
+
+    #[test]
+    fn ex07_classify() {
+        use crate::node::FunctionClass::*;
+        let classify = |expr: &str| {
+            expr.parse::<Tree>()
+                .expect("input is valid")
+                .classify()
+                .unwrap()
+        };
+        assert_eq!(classify("0"), Constant);
+        assert_eq!(classify("AA!|"), Constant);
+        assert_eq!(classify("A"), Literal);
+        assert_eq!(classify("AB&A|!"), Literal);
+        assert_eq!(classify("AB^"), Affine);
+        assert_eq!(classify("AB=C^"), Affine);
+        assert_eq!(classify("AB&"), Monotone);
+        assert_eq!(classify("AB&C|"), Monotone);
+        assert_eq!(classify("AB>"), General);
+        let anf = |expr: &str| expr.parse::<Tree>().expect("input is valid").anf().unwrap();
+        assert_eq!(anf("AB^"), [false, true, true, false]);
+        assert_eq!(anf("AB|"), [false, true, true, true]);
+        assert_eq!(anf("AB="), [true, true, true, false]);
+    }
+
+    #[test]
+    fn ex07_tree_clone() {
+        let tree = "AB&C|".parse::<Tree>().expect("input is valid");
+        tree.variables[0].set(Variable {
+            name: 'A',
+            value: true,
+        });
+        let copy = tree.clone();
+        assert_eq!(format!("{:?}", copy), format!("{:?}", tree));
+        // the values are copied, not shared
+        assert!(copy.variables[0].get().value);
+        copy.variables[1].set(Variable {
+            name: 'B',
+            value: true,
+        });
+        assert!(copy.root.eval());
+        assert!(!tree.root.eval());
+        assert!(!tree.variables[1].get().value);
+        assert_eq!(copy.truth_table().unwrap(), tree.truth_table().unwrap());
+    }
+
+    #[test]
+    fn ex07_influences() {
+        let influences = |expr: &str| expr.parse::<Tree>().expect("input is valid").influences();
+        assert_eq!(influences("AB^"), [('A', 1.0), ('B', 1.0)]);
+        assert_eq!(influences("AB&"), [('A', 0.5), ('B', 0.5)]);
+        assert_eq!(influences("AB&A|"), [('A', 1.0), ('B', 0.0)]);
+        // majority: a variable decides when the other two disagree
+        assert_eq!(
+            influences("AB&AC&|BC&|"),
+            [('A', 0.5), ('B', 0.5), ('C', 0.5)]
+        );
+        assert!(influences("1").is_empty());
+    }
+
+    #[test]
+    fn ex07_to_3cnf() {
+        use crate::expr_generator::{random_cnf, random_unsat_cnf};
+        use crate::node::ThreeCnfError;
+        let parse = |expr: &str| expr.parse::<Tree>().expect("input is valid");
+        let tree = parse("ABCD!E||||");
+        assert!(!tree.is_3cnf());
+        let three = tree.to_3cnf().unwrap();
+        assert!(three.is_3cnf());
+        assert_eq!(three.root.to_string(), "AB|F|F!C|G|&G!D!|E|&");
+        assert!(three.satisfy());
+        assert!(parse("AB|C!&").is_3cnf());
+        for seed in 0..20 {
+            let tree = random_cnf(6, 8, 5, seed);
+            let three = tree.to_3cnf().unwrap();
+            assert!(three.is_3cnf());
+            assert_eq!(three.satisfy_dpll().is_some(), tree.satisfy());
+            let unsat = random_unsat_cnf(4, 2, 4, seed).to_3cnf().unwrap();
+            assert!(unsat.is_3cnf());
+            assert_eq!(unsat.satisfy_dpll(), None);
+        }
+        assert_eq!(
+            random_cnf(20, 4, 20, 0).to_3cnf().err(),
+            Some(ThreeCnfError::OutOfVariables {
+                needed: 68,
+                free: 6
+            })
+        );
+    }
+
+    #[test]
+    fn ex07_dot_ranks() {
+        use crate::dot_graph::to_dot;
+        let tree = "AB&CD&|".parse::<Tree>().expect("input is valid");
+        let dot = to_dot(&tree.root);
+        assert!(dot.contains("\t{ rank=same; \"|_A\"; }\n"));
+        assert!(dot.contains("\t{ rank=same; \"&_A\"; \"&_B\"; }\n"));
+        assert!(dot.contains("\t{ rank=same; \"A_A\"; \"B_A\"; \"C_A\"; \"D_A\"; }\n"));
+        assert_eq!(dot.matches("rank=same").count(), 3);
+    }
+
+    #[test]
+    fn ex07_to_svg() {
+        use crate::dot_graph::to_svg;
+        let tree = "AB&C|".parse::<Tree>().expect("input is valid");
+        let svg = to_svg(&tree.root);
+        assert!(svg.starts_with(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"120\" height=\"150\">"
+        ));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("<circle").count(), 5);
+        assert_eq!(svg.matches("<text").count(), 5);
+        assert_eq!(svg.matches("<line").count(), 4);
+        assert!(svg.contains(">&amp;</text>"));
+        // the & is centered over A and B, the | over the & and C
+        assert!(svg.contains("<circle cx=\"40\" cy=\"75\""));
+        assert!(svg.contains("<circle cx=\"70\" cy=\"25\""));
+    }
+
+    #[test]
+    fn ex07_satisfying_set() {
+        use crate::node::BitsetError;
+        let set = |expr: &str| {
+            expr.parse::<Tree>()
+                .expect("input is valid")
+                .satisfying_set()
+        };
+        assert_eq!(set("AB&"), Ok(0b1000));
+        assert_eq!(set("AB|"), Ok(0b1110));
+        assert_eq!(set("AB&").unwrap() & !set("AB|").unwrap(), 0);
+        assert_eq!(set("A!"), Ok(0b01));
+        assert_eq!(set("ABCDEF|||||"), Ok(u64::MAX - 1));
+        assert_eq!(set("ABCDEFG||||||"), Err(BitsetError::TooManyVariables(7)));
+    }
+
+    #[test]
+    fn ex07_verify_model() {
+        let tree = "AB|C!&".parse::<Tree>().expect("input is valid");
+        assert!(tree.verify_model(&[('A', true), ('C', false)]));
+        assert!(tree.verify_model(&[('B', true)]));
+        assert!(!tree.verify_model(&[('A', true), ('C', true)]));
+        assert!(!tree.verify_model(&[]));
+        // a later entry wins, and variables not in the formula don't matter
+        assert!(tree.verify_model(&[('A', false), ('A', true), ('Z', true)]));
+        for _ in 0..100 {
+            let expr = random_rpn_expr(4, 4);
+            let tree = expr.parse::<Tree>().expect("input is valid");
+            if let Some(model) = tree.satisfy_dpll() {
+                assert!(tree.verify_model(&model), "{}", expr);
+            }
+        }
+    }
+
+    #[test]
+    fn ex07_truth_density() {
+        let density = |expr: &str| {
+            expr.parse::<Tree>()
+                .expect("input is valid")
+                .truth_density()
+                .unwrap()
+        };
+        assert_eq!(density("AB|"), (3, 4));
+        assert_eq!(density("AB&"), (1, 4));
+        assert_eq!(density("ABC^^"), (4, 8));
+        assert_eq!(density("AA!&"), (0, 2));
+        assert_eq!(density("1"), (1, 1));
+    }
+
+    #[test]
+    fn ex07_comments() {
+        use crate::node::parse_many;
+
+        let parse = |expr: &str| expr.parse::<Tree>().map(|tree| tree.root.to_string());
+        assert_eq!(parse("AB& # conjunction"), Ok("AB&".to_string()));
+        assert_eq!(parse("AB&#"), Ok("AB&".to_string()));
+        assert_eq!(
+            parse("A # the left side\nB # the right side\n|"),
+            Ok("AB|".to_string())
+        );
+        assert_eq!(
+            parse("# only a comment").err(),
+            Some(ParseError::UnbalancedExpression)
+        );
+        let trees = parse_many("# a header\nAB& # and\n\nAB| # or\n");
+        assert_eq!(trees.len(), 2);
+        assert_eq!(trees[1].as_ref().unwrap().root.to_string(), "AB|");
+    }
+
+    #[test]
+    fn ex07_rename_all() {
+        let mut tree = "AB&C|".parse::<Tree>().expect("input is valid");
+        tree.rename_all(|v| (b'Z' - (v as u8 - b'A')) as char);
+        assert_eq!(tree.root.to_string(), "ZY&X|");
+        assert_eq!(tree.varlist(), ['X', 'Y', 'Z']);
+
+        let mut tree = "AB&".parse::<Tree>().expect("input is valid");
+        tree.rename_all(|_| 'A');
+        assert_eq!(tree.root.to_string(), "AA&");
+        assert_eq!(tree.varlist(), ['A']);
+        assert_eq!(tree.root.clone().simplify().to_string(), "A");
+        // the merged variables share one cell
+        tree.variables[0].set(Variable {
+            name: 'A',
+            value: true,
+        });
+        assert!(tree.root.eval());
+    }
+
+    #[test]
+    fn ex07_suggest_var_order() {
+        // B decides 3 rows out of 4, C and A one, D none
+        let tree = "CA&B|DD!|&".parse::<Tree>().expect("input is valid");
+        assert_eq!(tree.suggest_var_order(), ['B', 'C', 'A', 'D']);
+        for _ in 0..100 {
+            let expr = random_rpn_expr(4, 4);
+            let tree = expr.parse::<Tree>().expect("input is valid");
+            let order = tree.suggest_var_order();
+            for (v, influence) in tree.influences() {
+                let count = order.iter().filter(|&&o| o == v).count();
+                assert_eq!(count, 1, "{} in {}", v, expr);
+                if influence == 0.0 {
+                    continue;
+                }
+                // essential variables all come before the others
+                let inessential = tree.influences().into_iter().filter(|i| i.1 == 0.0);
+                let position = |v| order.iter().position(|&o| o == v);
+                for (w, _) in inessential {
+                    assert!(position(v) < position(w), "{} {} in {}", v, w, expr);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn ex07_eval_bitsliced() {
+        // xorshift, for lanes that aren't all alike
+        let mut state = 0x9e37_79b9_7f4a_7c15u64;
+        let mut next = move || {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state
+        };
+        for _ in 0..200 {
+            let expr = random_rpn_expr(4, 4);
+            let tree = expr.parse::<Tree>().expect("input is valid");
+            let words: HashMap<char, u64> = tree.varlist().iter().map(|&v| (v, next())).collect();
+            let sliced = tree.root.eval_bitsliced(&words);
+            for lane in 0..64 {
+                for (&name, word) in &words {
+                    let value = word >> lane & 1 == 1;
+                    tree.variables[name as usize - 'A' as usize].set(Variable { name, value });
+                }
+                assert_eq!(sliced >> lane & 1 == 1, tree.root.eval(), "{}", expr);
+            }
+        }
+        let tree = "AB>1&".parse::<Tree>().expect("input is valid");
+        assert_eq!(tree.root.eval_bitsliced(&HashMap::new()), u64::MAX);
+        let words = HashMap::from([('A', 0b1100), ('B', 0b1010)]);
+        assert_eq!(tree.root.eval_bitsliced(&words), !0b0100);
+    }
+
+    #[test]
+    fn ex07_truth_table_bitsliced() {
+        for maxvars in 1..=6 {
+            for _ in 0..100 {
+                let expr = random_rpn_expr(5, maxvars);
+                let tree = expr.parse::<Tree>().expect("input is valid");
+                assert_eq!(
+                    tree.truth_table().unwrap(),
+                    tree.truth_table_scalar().unwrap(),
+                    "{}",
+                    expr
+                );
+            }
+        }
+        let tree = "10|".parse::<Tree>().expect("input is valid");
+        assert_eq!(tree.truth_table().unwrap(), [true]);
+        // every row of six variables, past the 32 bits of a smaller word
+        let tree = "ABCDEF&&&&&".parse::<Tree>().expect("input is valid");
+        let table = tree.truth_table().unwrap();
+        assert_eq!(table.len(), 64);
+        assert_eq!(table.iter().position(|&res| res), Some(63));
+        // seven variables take the scalar loop
+        let tree = "ABCDEFG||||||".parse::<Tree>().expect("input is valid");
+        assert_eq!(
+            tree.truth_table().unwrap(),
+            tree.truth_table_scalar().unwrap()
+        );
+    }
+
+    #[test]
+    fn ex07_eval_short_circuit() {
+        // evaluates both operands every time
+        fn reference(node: &Node) -> bool {
+            node.fold(|c| c, |_| unreachable!(), |n| !n, |op, l, r| op.apply(l, r))
+        }
+        for _ in 0..500 {
+            let expr = random_rpn_expr(4, 3);
+            let tree = expr.parse::<Tree>().expect("input is valid");
+            for row in 0..1 << tree.varlist().len() {
+                // the same row as constants, for the reference
+                let mut node = tree.root.clone();
+                for (j, &name) in tree.varlist().iter().rev().enumerate() {
+                    let value = row >> j & 1 == 1;
+                    tree.variables[name as usize - 'A' as usize].set(Variable { name, value });
+                    node = node.cofactor(name, value);
+                }
+                assert_eq!(tree.root.eval(), reference(&node), "{}", expr);
+            }
+        }
+        // A & (B & (B & ...)) nests to the right, and is cut short at A
+        let deep = format!("A{}{}", "B".repeat(50_000), "&".repeat(50_000));
+        let tree = deep.parse::<Tree>().expect("input is valid");
+        assert!(!tree.root.eval());
+        tree.variables[0].set(Variable {
+            name: 'A',
+            value: true,
+        });
+        tree.variables[1].set(Variable {
+            name: 'B',
+            value: true,
+        });
+        assert!(tree.root.eval());
+    }
+
+    #[test]
+    fn ex07_deep_count_models() {
+        // deep enough to overflow a recursive evaluation, few enough variables for one word
+        let tree = format!("A{}", "!".repeat(50_000)).parse::<Tree>().unwrap();
+        assert_eq!(tree.count_models().unwrap(), 1);
+        assert_eq!(tree.truth_table().unwrap(), [false, true]);
+        let deep = format!("A{}", "B|".repeat(50_000));
+        assert_eq!(deep.parse::<Tree>().unwrap().count_models().unwrap(), 3);
+    }
+
+    #[test]
+    fn ex07_table_too_many_variables() {
+        use crate::node::TableError::TooManyVariables;
+        use crate::truth_table::TruthTable;
+
+        let wide = format!("{}{}", ('A'..='U').collect::<String>(), "|".repeat(20));
+        let tree = wide.parse::<Tree>().expect("input is valid");
+        let err = Some(TooManyVariables(21));
+        assert_eq!(tree.truth_table().err(), err);
+        assert_eq!(tree.truth_table_scalar().err(), err);
+        assert_eq!(tree.count_models().err(), err);
+        assert_eq!(tree.truth_density().err(), err);
+        assert_eq!(tree.essential_variables().err(), err);
+        assert_eq!(tree.core_model_count().err(), err);
+        assert_eq!(tree.eval_all().err(), err);
+        assert_eq!(tree.walsh_spectrum().err(), err);
+        assert_eq!(tree.anf().err(), err);
+        assert_eq!(tree.is_affine().err(), err);
+        assert!(tree.classify().is_err());
+        assert!(tree.canonical_cnf().is_err());
+        assert_eq!(TruthTable::from_formula(&tree).err(), err);
+        // one variable less is still a table
+        let tree = wide[1..wide.len() - 1]
+            .parse::<Tree>()
+            .expect("input is valid");
+        assert_eq!(tree.count_models(), Ok((1 << 20) - 1));
+    }
 }
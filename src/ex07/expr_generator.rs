@@ -1,4 +1,5 @@
-use crate::node::{BinOp, Node, VarCell, Variable};
+use crate::cnf_builder::CnfBuilder;
+use crate::node::{BinOp, Node, Tree, VarCell, Variable};
 use std::cell::Cell;
 use std::fs::File;
 use std::io::Read;
@@ -54,3 +55,79 @@ fn random_node(vals: &[VarCell], maxdepth: u32) -> Node {
         },
     }
 }
+
+// splitmix64, so the same seed always gives the same instance
+struct SeededRng(u64);
+
+impl SeededRng {
+    fn next(&mut self) -> usize {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        (z ^ (z >> 31)) as usize
+    }
+
+    // `len` distinct variables among the first `num_vars` letters
+    fn pick_vars(&mut self, num_vars: usize, len: usize) -> Vec<char> {
+        let mut vars: Vec<char> = ('A'..='Z').take(num_vars).collect();
+        for i in 0..len {
+            let j = i + self.next() % (num_vars - i);
+            vars.swap(i, j);
+        }
+        vars.truncate(len);
+        vars
+    }
+}
+
+fn add_random_clauses(
+    cnf: &mut CnfBuilder,
+    rng: &mut SeededRng,
+    num_vars: usize,
+    num_clauses: usize,
+    clause_len: usize,
+) {
+    assert!(num_vars <= 26, "only 26 variables are available");
+    assert!(clause_len <= num_vars, "clause_len must be <= num_vars");
+    for _ in 0..num_clauses {
+        let clause: Vec<(char, bool)> = rng
+            .pick_vars(num_vars, clause_len)
+            .into_iter()
+            .map(|v| (v, rng.next().is_multiple_of(2)))
+            .collect();
+        cnf.add_clause(&clause);
+    }
+}
+
+// `num_clauses` clauses of `clause_len` distinct variables, signs drawn at random
+#[allow(dead_code)]
+pub fn random_cnf(num_vars: usize, num_clauses: usize, clause_len: usize, seed: u64) -> Tree {
+    let mut cnf = CnfBuilder::new();
+    add_random_clauses(
+        &mut cnf,
+        &mut SeededRng(seed),
+        num_vars,
+        num_clauses,
+        clause_len,
+    );
+    cnf.build()
+}
+
+// the same, plus every one of the 2^clause_len sign combinations over some clause_len variables,
+// which no assignment can satisfy all at once
+#[allow(dead_code)]
+pub fn random_unsat_cnf(num_vars: usize, num_clauses: usize, clause_len: usize, seed: u64) -> Tree {
+    let mut cnf = CnfBuilder::new();
+    let mut rng = SeededRng(seed);
+    add_random_clauses(&mut cnf, &mut rng, num_vars, num_clauses, clause_len);
+    let vars = rng.pick_vars(num_vars, clause_len);
+    for signs in 0..1usize << clause_len {
+        let clause: Vec<(char, bool)> = vars
+            .iter()
+            .enumerate()
+            .map(|(j, &v)| (v, (signs >> j) & 1 == 1))
+            .collect();
+        cnf.add_clause(&clause);
+    }
+    cnf.build()
+}
@@ -1,11 +1,16 @@
+use crate::cached_eval::CachedEval;
+use crate::cnf_builder::CnfBuilder;
+use crate::ex06_node;
 use std::cell::Cell;
+use std::collections::{HashMap, HashSet};
+use std::convert::Infallible;
 use std::fmt;
 use std::rc::Rc;
 use BinOp::*;
 use Node::*;
 use ParseError::*;
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
 pub enum BinOp {
     And,
     Or,
@@ -34,6 +39,73 @@ pub enum Node {
     Const(bool),
 }
 
+// dropping a node would recurse as deep as the tree, so the children are taken apart with a
+// stack instead, a child without children of its own being left for its parent's drop
+impl Drop for Node {
+    fn drop(&mut self) {
+        let mut stack = Vec::new();
+        self.take_children(&mut stack);
+        while let Some(mut node) = stack.pop() {
+            node.take_children(&mut stack);
+        }
+    }
+}
+
+impl Node {
+    fn take_children(&mut self, stack: &mut Vec<Node>) {
+        let mut take = |child: &mut Box<Node>| {
+            if matches!(**child, Not(_) | Binary { .. }) {
+                stack.push(std::mem::replace(&mut **child, Const(false)));
+            }
+        };
+        match self {
+            Not(operand) => take(operand),
+            Binary { left, right, .. } => {
+                take(left);
+                take(right);
+            }
+            Var(_) | Const(_) => (),
+        }
+    }
+
+    // the node by value, its fields out: Node implementing Drop, a match can't move them itself
+    pub fn into_parts(mut self) -> NodeParts {
+        match &mut self {
+            Const(c) => NodeParts::Const(*c),
+            Var(v) => NodeParts::Var(v.clone()),
+            Not(operand) => NodeParts::Not(std::mem::replace(operand, Box::new(Const(false)))),
+            Binary { op, left, right } => NodeParts::Binary {
+                op: *op,
+                left: std::mem::replace(left, Box::new(Const(false))),
+                right: std::mem::replace(right, Box::new(Const(false))),
+            },
+        }
+    }
+}
+
+// the fields of a node, moved out by into_parts
+pub enum NodeParts {
+    Binary {
+        op: BinOp,
+        left: Box<Node>,
+        right: Box<Node>,
+    },
+    Not(Box<Node>),
+    Var(VarCell),
+    Const(bool),
+}
+
+impl From<NodeParts> for Node {
+    fn from(parts: NodeParts) -> Node {
+        match parts {
+            NodeParts::Binary { op, left, right } => Binary { op, left, right },
+            NodeParts::Not(operand) => Not(operand),
+            NodeParts::Var(v) => Var(v),
+            NodeParts::Const(c) => Const(c),
+        }
+    }
+}
+
 pub struct Tree {
     pub root: Node,
     pub variables: Vec<VarCell>,
@@ -47,6 +119,50 @@ pub enum ParseError {
     UnbalancedExpression,
 }
 
+// selects which families of rules simplify_with applies, combine them with |
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub struct SimplifyRules(u8);
+
+impl SimplifyRules {
+    // folds operations with a constant operand, ex: A1& -> A, 1! -> 0
+    pub const CONSTANTS: SimplifyRules = SimplifyRules(1);
+    // removes double negations, ex: A!! -> A
+    pub const DOUBLE_NEGATION: SimplifyRules = SimplifyRules(1 << 1);
+    // folds operations on equal operands, ex: AA& -> A, AA^ -> 0
+    pub const EQUAL_OPERANDS: SimplifyRules = SimplifyRules(1 << 2);
+
+    pub fn all() -> SimplifyRules {
+        SimplifyRules::CONSTANTS | SimplifyRules::DOUBLE_NEGATION | SimplifyRules::EQUAL_OPERANDS
+    }
+
+    pub fn empty() -> SimplifyRules {
+        SimplifyRules(0)
+    }
+
+    pub fn contains(self, other: SimplifyRules) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl std::ops::BitOr for SimplifyRules {
+    type Output = SimplifyRules;
+    fn bitor(self, other: SimplifyRules) -> SimplifyRules {
+        SimplifyRules(self.0 | other.0)
+    }
+}
+
+impl BinOp {
+    pub fn apply(self, left: bool, right: bool) -> bool {
+        match self {
+            And => left && right,
+            Or => left || right,
+            Impl => !left || right,
+            Leq => left == right,
+            Xor => left ^ right,
+        }
+    }
+}
+
 impl TryFrom<char> for BinOp {
     type Error = ParseError;
 
@@ -60,205 +176,2336 @@ impl TryFrom<char> for BinOp {
             _ => Err(InvalidCharacter(c)),
         }
     }
-}
+}
+
+impl From<BinOp> for char {
+    fn from(op: BinOp) -> Self {
+        match op {
+            And => '&',
+            Or => '|',
+            Xor => '^',
+            Impl => '>',
+            Leq => '=',
+        }
+    }
+}
+
+impl fmt::Display for BinOp {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", char::from(*self))
+    }
+}
+
+// the symbols of the rpn, without the lexing
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum Token {
+    Var(char),
+    Const(bool),
+    Op(BinOp),
+    Not,
+}
+
+// the token as it's written in the rpn
+impl fmt::Debug for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Var(c) => write!(f, "{}", c),
+            Token::Const(c) => write!(f, "{}", *c as u8),
+            Token::Op(op) => write!(f, "{}", op),
+            Token::Not => write!(f, "!"),
+        }
+    }
+}
+
+// builds a node from rpn tokens, with variables of its own
+#[allow(dead_code)]
+pub fn from_postfix_tokens(tokens: &[Token]) -> Result<Node, ParseError> {
+    let variables = new_variables();
+    let mut stack = Vec::with_capacity(tokens.len());
+    for token in tokens {
+        match *token {
+            Token::Const(c) => stack.push(Const(c)),
+            Token::Var(c) if c.is_ascii_uppercase() => {
+                stack.push(Var(variables[c as usize - 'A' as usize].clone()))
+            }
+            Token::Var(c) => return Err(InvalidCharacter(c)),
+            Token::Not => {
+                let operand = stack.pop().ok_or(MissingOperand)?;
+                stack.push(Not(Box::new(operand)));
+            }
+            Token::Op(op) => {
+                let right = stack.pop().ok_or(MissingOperand)?;
+                let left = stack.pop().ok_or(MissingOperand)?;
+                stack.push(Binary {
+                    op,
+                    left: Box::new(left),
+                    right: Box::new(right),
+                });
+            }
+        }
+    }
+    match (stack.pop(), stack.is_empty()) {
+        (Some(node), true) => Ok(node),
+        _ => Err(UnbalancedExpression),
+    }
+}
+
+// a post-order walk with an explicit stack, for trees too deep to recurse on
+// operators come out after their operands, like in the rpn
+enum Step<'a> {
+    Visit(&'a Node),
+    Not,
+    Op(BinOp),
+}
+
+impl Node {
+    // stops at the first error of `visit` and returns it
+    fn try_walk<E>(&self, mut visit: impl FnMut(&Step<'_>) -> Result<(), E>) -> Result<(), E> {
+        let mut stack = vec![Step::Visit(self)];
+        while let Some(step) = stack.pop() {
+            match step {
+                Step::Visit(Not(operand)) => {
+                    stack.push(Step::Not);
+                    stack.push(Step::Visit(operand));
+                }
+                Step::Visit(Binary { op, left, right }) => {
+                    stack.push(Step::Op(*op));
+                    stack.push(Step::Visit(right));
+                    stack.push(Step::Visit(left));
+                }
+                _ => visit(&step)?,
+            }
+        }
+        Ok(())
+    }
+
+    fn walk(&self, mut visit: impl FnMut(&Step<'_>)) {
+        let Ok(()) = self.try_walk(|step| {
+            visit(step);
+            Ok::<(), Infallible>(())
+        });
+    }
+}
+
+// a subformula, its operands being numbered by shape_id
+#[derive(PartialEq, Eq, Hash)]
+enum Shape {
+    Const(bool),
+    Var(char),
+    Not(usize),
+    Op(BinOp, usize, usize),
+}
+
+impl Node {
+    // numbers the subformulas in `ids`, the same number meaning equal up to the order of
+    // commutative operands, like ==, but in one walk where == compares the operands again
+    // at every level to sort them
+    fn shape_id(&self, ids: &mut HashMap<Shape, usize>) -> usize {
+        let mut operands = Vec::new();
+        self.walk(|step| {
+            let shape = match step {
+                Step::Visit(Const(c)) => Shape::Const(*c),
+                Step::Visit(Var(v)) => Shape::Var(v.get().name),
+                Step::Not => Shape::Not(operands.pop().unwrap()),
+                Step::Op(op) => {
+                    let right = operands.pop().unwrap();
+                    let left = operands.pop().unwrap();
+                    if *op != Impl && left > right {
+                        Shape::Op(*op, right, left)
+                    } else {
+                        Shape::Op(*op, left, right)
+                    }
+                }
+                Step::Visit(_) => unreachable!("walk only yields leaves"),
+            };
+            let next = ids.len();
+            operands.push(*ids.entry(shape).or_insert(next));
+        });
+        operands[0]
+    }
+}
+
+impl Node {
+    // the rpn as tokens, the same order as Display
+    pub fn to_postfix_tokens(&self) -> Vec<Token> {
+        let mut tokens = Vec::new();
+        self.walk(|step| {
+            tokens.push(match step {
+                Step::Visit(Var(v)) => Token::Var(v.get().name),
+                Step::Visit(Const(c)) => Token::Const(*c),
+                Step::Not => Token::Not,
+                Step::Op(op) => Token::Op(*op),
+                Step::Visit(_) => unreachable!("walk only yields leaves"),
+            });
+        });
+        tokens
+    }
+}
+
+impl fmt::Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.try_walk(|step| match step {
+            Step::Visit(Var(val)) => write!(f, "{}", val.get().name),
+            Step::Visit(Const(val)) => write!(f, "{}", *val as u8),
+            Step::Not => write!(f, "!"),
+            Step::Op(op) => write!(f, "{}", op),
+            Step::Visit(_) => unreachable!("walk only yields leaves"),
+        })
+    }
+}
+
+// the formula and the variables it uses, not all 26 cells
+impl fmt::Debug for Tree {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Tree {{ root: {}, variables: {:?} }}",
+            self.root, self.varlist
+        )
+    }
+}
+
+// constants, then variables, then !, then operators, each compared with their own kind
+// the operands of everything but > are compared as a sorted pair, so AB& == BA& like equals
+impl Ord for Node {
+    fn cmp(&self, other: &Node) -> std::cmp::Ordering {
+        fn rank(node: &Node) -> u8 {
+            match node {
+                Const(_) => 0,
+                Var(_) => 1,
+                Not(_) => 2,
+                Binary { .. } => 3,
+            }
+        }
+        fn operands<'a>(op: BinOp, left: &'a Node, right: &'a Node) -> (&'a Node, &'a Node) {
+            if op != Impl && left > right {
+                (right, left)
+            } else {
+                (left, right)
+            }
+        }
+        match (self, other) {
+            (Const(a), Const(b)) => a.cmp(b),
+            (Var(a), Var(b)) => a.get().name.cmp(&b.get().name),
+            (Not(a), Not(b)) => a.cmp(b),
+            (
+                Binary { op, left, right },
+                Binary {
+                    op: o,
+                    left: l,
+                    right: r,
+                },
+            ) => op
+                .cmp(o)
+                .then_with(|| operands(*op, left, right).cmp(&operands(*o, l, r))),
+            _ => rank(self).cmp(&rank(other)),
+        }
+    }
+}
+
+impl PartialOrd for Node {
+    fn partial_cmp(&self, other: &Node) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Node {
+    fn eq(&self, other: &Node) -> bool {
+        self.cmp(other) == std::cmp::Ordering::Equal
+    }
+}
+
+impl Eq for Node {}
+
+impl fmt::Debug for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MissingOperand => write!(f, "Missing operand"),
+            InvalidCharacter(c) => write!(f, "Invalid character: '{}'", c),
+            UnbalancedExpression => write!(f, "Unbalanced expression"),
+        }
+    }
+}
+
+// values given to named variables, ex: [(A, true), (B, false)]
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct Assignment(Vec<(char, bool)>);
+
+#[allow(dead_code)]
+#[derive(PartialEq, Eq)]
+pub enum AssignmentError {
+    LengthMismatch { vars: usize, values: usize },
+    DuplicateVariable(char),
+}
+
+impl fmt::Debug for AssignmentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AssignmentError::LengthMismatch { vars, values } => {
+                write!(f, "{} variables for {} values", vars, values)
+            }
+            AssignmentError::DuplicateVariable(c) => write!(f, "Duplicate variable: '{}'", c),
+        }
+    }
+}
+
+impl TryFrom<(&[char], Vec<bool>)> for Assignment {
+    type Error = AssignmentError;
+
+    fn try_from((vars, values): (&[char], Vec<bool>)) -> Result<Self, Self::Error> {
+        if vars.len() != values.len() {
+            return Err(AssignmentError::LengthMismatch {
+                vars: vars.len(),
+                values: values.len(),
+            });
+        }
+        for (i, &v) in vars.iter().enumerate() {
+            if vars[..i].contains(&v) {
+                return Err(AssignmentError::DuplicateVariable(v));
+            }
+        }
+        Ok(Assignment(vars.iter().copied().zip(values).collect()))
+    }
+}
+
+#[allow(dead_code)]
+impl Assignment {
+    pub fn get(&self, name: char) -> Option<bool> {
+        self.0
+            .iter()
+            .find(|(v, _)| *v == name)
+            .map(|&(_, value)| value)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(char, bool)> {
+        self.0.iter()
+    }
+}
+
+// panics if the variable isn't assigned, like a HashMap
+impl std::ops::Index<char> for Assignment {
+    type Output = bool;
+
+    fn index(&self, name: char) -> &bool {
+        self.0
+            .iter()
+            .find(|(v, _)| *v == name)
+            .map(|(_, value)| value)
+            .unwrap_or_else(|| panic!("'{}' is not assigned", name))
+    }
+}
+
+// shortest_equivalent searches every formula, only for a few variables
+pub const SHORTEST_MAX_VARS: usize = 3;
+// and gives up past this many symbols
+pub const SHORTEST_MAX_LEN: usize = 15;
+
+// functions building whole tables refuse formulas with more variables than this
+pub const MAX_TABLE_VARS: usize = 20;
+
+#[derive(PartialEq, Eq)]
+pub enum TableError {
+    TooManyVariables(usize),
+}
+
+impl fmt::Debug for TableError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TableError::TooManyVariables(n) => write!(
+                f,
+                "Too many variables for a table: {} (max {})",
+                n, MAX_TABLE_VARS
+            ),
+        }
+    }
+}
+
+// a table of up to this many variables fits the bits of a u64
+pub const BITSET_MAX_VARS: usize = 6;
+
+// bit i of LANE_PATTERNS[k] is bit k of i: the column of the table's kth variable from the right
+const LANE_PATTERNS: [u64; BITSET_MAX_VARS] = [
+    0xaaaa_aaaa_aaaa_aaaa,
+    0xcccc_cccc_cccc_cccc,
+    0xf0f0_f0f0_f0f0_f0f0,
+    0xff00_ff00_ff00_ff00,
+    0xffff_0000_ffff_0000,
+    0xffff_ffff_0000_0000,
+];
+
+#[allow(dead_code)]
+#[derive(PartialEq, Eq)]
+pub enum BitsetError {
+    TooManyVariables(usize),
+}
+
+impl fmt::Debug for BitsetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BitsetError::TooManyVariables(n) => write!(
+                f,
+                "Too many variables for a u64: {} (max {})",
+                n, BITSET_MAX_VARS
+            ),
+        }
+    }
+}
+
+// the assignment of a truth table row, the first variable being the MSB
+pub fn row_assignment(row: usize, n: usize) -> Vec<bool> {
+    (0..n).map(|j| (row >> (n - j - 1)) & 1 == 1).collect()
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Eq)]
+pub enum DepthError {
+    TooDeep { depth: usize, max_depth: usize },
+}
+
+impl fmt::Debug for DepthError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DepthError::TooDeep { depth, max_depth } => {
+                write!(f, "Formula too deep: {} (max {})", depth, max_depth)
+            }
+        }
+    }
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Eq)]
+pub enum SynthError {
+    TableLength { expected: usize, found: usize },
+    InvalidVariable(char),
+    DuplicateVariable(char),
+    TooManyVariables { found: usize, max: usize },
+    MintermOutOfRange(usize),
+}
+
+impl fmt::Debug for SynthError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SynthError::TableLength { expected, found } => {
+                write!(f, "Table length: expected {}, found {}", expected, found)
+            }
+            SynthError::InvalidVariable(c) => write!(f, "Invalid variable: '{}'", c),
+            SynthError::DuplicateVariable(c) => write!(f, "Duplicate variable: '{}'", c),
+            SynthError::TooManyVariables { found, max } => {
+                write!(f, "Too many variables: {} (max {})", found, max)
+            }
+            SynthError::MintermOutOfRange(row) => write!(f, "Minterm out of range: {}", row),
+        }
+    }
+}
+
+// the simplest family a function belongs to, from the most to the least specific
+#[allow(dead_code)]
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum FunctionClass {
+    Constant,
+    Literal,
+    Affine,
+    Monotone,
+    General,
+}
+
+impl fmt::Debug for FunctionClass {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FunctionClass::Constant => write!(f, "Constant"),
+            FunctionClass::Literal => write!(f, "Literal"),
+            FunctionClass::Affine => write!(f, "Affine"),
+            FunctionClass::Monotone => write!(f, "Monotone"),
+            FunctionClass::General => write!(f, "General"),
+        }
+    }
+}
+
+#[allow(dead_code)]
+#[derive(PartialEq, Eq)]
+pub enum ThreeCnfError {
+    OutOfVariables { needed: usize, free: usize },
+}
+
+impl fmt::Debug for ThreeCnfError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ThreeCnfError::OutOfVariables { needed, free } => write!(
+                f,
+                "Out of variables: {} auxiliary needed, {} left",
+                needed, free
+            ),
+        }
+    }
+}
+
+// a variable and whether it's positive, as in the clauses of cnf_clauses
+pub type Literal = (char, bool);
+
+#[allow(dead_code)]
+#[derive(PartialEq, Eq)]
+pub enum TwoSatError {
+    ClauseLength(usize),
+}
+
+impl fmt::Debug for TwoSatError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TwoSatError::ClauseLength(n) => {
+                write!(f, "Clause of {} literals, 2-sat needs 1 or 2", n)
+            }
+        }
+    }
+}
+
+// ors together the minterms (rows, the first variable being the MSB) as ands of literals
+fn sum_of_minterms(
+    minterms: impl Iterator<Item = usize>,
+    vars: &[char],
+    variables: &[VarCell],
+) -> Node {
+    minterms
+        .map(|row| {
+            row_assignment(row, vars.len())
+                .into_iter()
+                .zip(vars)
+                .map(|(value, &name)| {
+                    let var = Var(variables[name as usize - 'A' as usize].clone());
+                    if value {
+                        var
+                    } else {
+                        Not(Box::new(var))
+                    }
+                })
+                .reduce(|left, right| *(Box::new(left) & Box::new(right)))
+                .unwrap_or(Const(true))
+        })
+        .reduce(|left, right| *(Box::new(left) | Box::new(right)))
+        .unwrap_or(Const(false))
+}
+
+// ands together the maxterms (rows, the first variable being the MSB) as ors of literals
+// each clause is false on its row only
+fn product_of_maxterms(
+    maxterms: impl Iterator<Item = usize>,
+    vars: &[char],
+    variables: &[VarCell],
+) -> Node {
+    maxterms
+        .map(|row| {
+            row_assignment(row, vars.len())
+                .into_iter()
+                .zip(vars)
+                .map(|(value, &name)| {
+                    let var = Var(variables[name as usize - 'A' as usize].clone());
+                    if value {
+                        Not(Box::new(var))
+                    } else {
+                        var
+                    }
+                })
+                .reduce(|left, right| *(Box::new(left) | Box::new(right)))
+                .unwrap_or(Const(false))
+        })
+        .reduce(|left, right| *(Box::new(left) & Box::new(right)))
+        .unwrap_or(Const(true))
+}
+
+// a product of literals as the rows it covers: the bits of `mask` are free, the others are
+// those of `value`, ex: A!C over ABC -> value 001, mask 010
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct Cube {
+    value: usize,
+    mask: usize,
+}
+
+impl Cube {
+    fn covers(self, row: usize) -> bool {
+        row & !self.mask == self.value
+    }
+}
+
+// the Quine-McCluskey merging: cubes differing in one fixed bit merge into one with that bit
+// free, until none merges, the cubes left over being the prime implicants
+// they are yielded after each round, before the next one builds bigger cubes
+struct PrimeImplicants {
+    cubes: Vec<Cube>,
+    num_vars: usize,
+    primes: std::vec::IntoIter<Cube>,
+}
+
+impl PrimeImplicants {
+    fn new(minterms: &[usize], num_vars: usize) -> PrimeImplicants {
+        PrimeImplicants {
+            cubes: minterms
+                .iter()
+                .map(|&value| Cube { value, mask: 0 })
+                .collect(),
+            num_vars,
+            primes: Vec::new().into_iter(),
+        }
+    }
+
+    // one round: the cubes that merged with none are prime, the merged ones are the next cubes
+    fn merge(&mut self) {
+        let set: HashSet<Cube> = self.cubes.iter().copied().collect();
+        let mut merged = HashSet::new();
+        let mut next = HashSet::new();
+        for &cube in &self.cubes {
+            for bit in (0..self.num_vars)
+                .map(|i| 1 << i)
+                .filter(|bit| cube.mask & bit == 0)
+            {
+                let partner = Cube {
+                    value: cube.value ^ bit,
+                    mask: cube.mask,
+                };
+                if set.contains(&partner) {
+                    merged.insert(cube);
+                    next.insert(Cube {
+                        value: cube.value & !bit,
+                        mask: cube.mask | bit,
+                    });
+                }
+            }
+        }
+        let primes: Vec<Cube> = self
+            .cubes
+            .iter()
+            .copied()
+            .filter(|cube| !merged.contains(cube))
+            .collect();
+        self.primes = primes.into_iter();
+        self.cubes = next.into_iter().collect();
+        self.cubes.sort_unstable();
+    }
+}
+
+impl Iterator for PrimeImplicants {
+    type Item = Cube;
+
+    fn next(&mut self) -> Option<Cube> {
+        loop {
+            if let Some(prime) = self.primes.next() {
+                return Some(prime);
+            }
+            if self.cubes.is_empty() {
+                return None;
+            }
+            self.merge();
+        }
+    }
+}
+
+fn prime_implicants(minterms: &[usize], num_vars: usize) -> Vec<Cube> {
+    PrimeImplicants::new(minterms, num_vars).collect()
+}
+
+// the prime implicants needed to cover every minterm: the essential ones, the only to cover
+// some minterm, then greedily the one covering the most minterms left
+// the greedy part can miss the smallest cover, finding it is NP-hard
+fn implicant_cover(minterms: &[usize], num_vars: usize) -> Vec<Cube> {
+    let primes = prime_implicants(minterms, num_vars);
+    let mut cover: Vec<Cube> = Vec::new();
+    for &row in minterms {
+        let mut covering = primes.iter().filter(|prime| prime.covers(row));
+        if let (Some(&prime), None) = (covering.next(), covering.next()) {
+            if !cover.contains(&prime) {
+                cover.push(prime);
+            }
+        }
+    }
+    let mut left: Vec<usize> = minterms
+        .iter()
+        .copied()
+        .filter(|&row| !cover.iter().any(|cube| cube.covers(row)))
+        .collect();
+    while !left.is_empty() {
+        // the most minterms, then the fewest literals
+        let &best = primes
+            .iter()
+            .max_by_key(|prime| {
+                let covered = left.iter().filter(|&&row| prime.covers(row)).count();
+                (covered, prime.mask.count_ones())
+            })
+            .expect("every minterm has a prime implicant");
+        left.retain(|&row| !best.covers(row));
+        cover.push(best);
+    }
+    cover
+}
+
+// the terms in the order of their literals, variable by variable: A, then A!, then neither
+fn sort_cover(cover: &mut [Cube], num_vars: usize) {
+    cover.sort_unstable_by_key(|cube| {
+        (0..num_vars)
+            .rev()
+            .map(|i| (cube.mask >> i & 1, cube.value >> i & 1 == 0))
+            .collect::<Vec<_>>()
+    });
+}
+
+// the literals a cube fixes, vars[0] being the MSB, ex: value 001 mask 010 over ABC -> A! C
+fn cube_literals<'a>(
+    cube: Cube,
+    vars: &'a [char],
+    variables: &'a [VarCell],
+) -> impl Iterator<Item = Node> + 'a {
+    vars.iter()
+        .enumerate()
+        .map(|(i, &name)| (1 << (vars.len() - i - 1), name))
+        .filter(move |&(bit, _)| cube.mask & bit == 0)
+        .map(move |(bit, name)| {
+            let var = Var(variables[name as usize - 'A' as usize].clone());
+            if cube.value & bit != 0 {
+                var
+            } else {
+                Not(Box::new(var))
+            }
+        })
+}
+
+// the and of the literals a cube fixes, ex: value 001 mask 010 over ABC -> A!C&
+fn product_of_cube(cube: Cube, vars: &[char], variables: &[VarCell]) -> Node {
+    cube_literals(cube, vars, variables)
+        .reduce(|left, right| *(Box::new(left) & Box::new(right)))
+        .unwrap_or(Const(true))
+}
+
+// the or of the products of a cover of the minterms, the minimized dnf
+fn minimized_dnf(minterms: &[usize], vars: &[char], variables: &[VarCell]) -> Node {
+    let mut cover = implicant_cover(minterms, vars.len());
+    sort_cover(&mut cover, vars.len());
+    cover
+        .into_iter()
+        .map(|cube| product_of_cube(cube, vars, variables))
+        .reduce(|left, right| *(Box::new(left) | Box::new(right)))
+        .unwrap_or(Const(false))
+}
+
+// the and of the clauses false on a cover of the maxterms, the minimized cnf: a cube of
+// false rows is the clause with its literals complemented, ex: A!B!& -> AB|
+fn minimized_cnf(maxterms: &[usize], vars: &[char], variables: &[VarCell]) -> Node {
+    let all = (1 << vars.len()) - 1;
+    let mut clauses: Vec<Cube> = implicant_cover(maxterms, vars.len())
+        .into_iter()
+        .map(|cube| Cube {
+            value: !cube.value & !cube.mask & all,
+            mask: cube.mask,
+        })
+        .collect();
+    sort_cover(&mut clauses, vars.len());
+    clauses
+        .into_iter()
+        .map(|cube| {
+            cube_literals(cube, vars, variables)
+                .reduce(|left, right| *(Box::new(left) | Box::new(right)))
+                .unwrap_or(Const(false))
+        })
+        .reduce(|left, right| *(Box::new(left) & Box::new(right)))
+        .unwrap_or(Const(true))
+}
+
+// builds a minimized formula whose truth table (vars[0] being the MSB) is `table`
+// an or of products covering the true rows with prime implicants, ex: the majority of ABC
+// -> AB&AC&|BC&|, where the sum of its minterms has four products of three literals
+#[allow(dead_code)]
+pub fn formula_from_table(table: &[bool], vars: &[char]) -> Result<Tree, SynthError> {
+    for (i, &v) in vars.iter().enumerate() {
+        if !v.is_ascii_uppercase() {
+            return Err(SynthError::InvalidVariable(v));
+        }
+        if vars[..i].contains(&v) {
+            return Err(SynthError::DuplicateVariable(v));
+        }
+    }
+    if vars.len() > MAX_TABLE_VARS {
+        return Err(SynthError::TooManyVariables {
+            found: vars.len(),
+            max: MAX_TABLE_VARS,
+        });
+    }
+    if table.len() != 1 << vars.len() {
+        return Err(SynthError::TableLength {
+            expected: 1 << vars.len(),
+            found: table.len(),
+        });
+    }
+    let variables = new_variables();
+    let minterms: Vec<usize> = (0..table.len()).filter(|&row| table[row]).collect();
+    let root = minimized_dnf(&minterms, vars, &variables);
+    Ok(Tree::from_root(root, variables))
+}
+
+// the number of true rows, the models of the formula the table comes from
+pub fn truth_table_weight(table: &[bool]) -> usize {
+    table.iter().filter(|&&b| b).count()
+}
+
+// one line of a file of formulas, None when it's blank or only a comment
+pub fn parse_line(line: &str) -> Option<Result<Tree, ParseError>> {
+    let line = strip_comment(line);
+    (!line.is_empty()).then(|| line.parse())
+}
+
+// one formula per line, each parsed on its own so a bad line doesn't stop the others
+// blank lines and lines with only a comment are skipped
+#[allow(dead_code)]
+pub fn parse_many(input: &str) -> Vec<Result<Tree, ParseError>> {
+    input.lines().filter_map(parse_line).collect()
+}
+
+// whether two formulas in rpn denote the same function
+#[allow(dead_code)]
+pub fn are_equivalent(a: &str, b: &str) -> Result<bool, ParseError> {
+    Ok(a.parse::<Tree>()?.logically_eq(&b.parse::<Tree>()?))
+}
+
+// the assignments, over the union of the variables, where two formulas disagree
+// with the result of each, empty if they are equivalent
+#[allow(dead_code)]
+pub fn truth_table_diff(a: &str, b: &str) -> Result<Vec<(Vec<bool>, bool, bool)>, ParseError> {
+    let a = a.parse::<Tree>()?;
+    let b = b.parse::<Tree>()?;
+    let mut vars = a.varlist.clone();
+    vars.extend(&b.varlist);
+    vars.sort_unstable();
+    vars.dedup();
+    Ok((0..1 << vars.len())
+        .filter_map(|row| {
+            let assignment = row_assignment(row, vars.len());
+            for (&name, &value) in vars.iter().zip(&assignment) {
+                a.set_var(name, value);
+                b.set_var(name, value);
+            }
+            let (res_a, res_b) = (a.root.eval(), b.root.eval());
+            (res_a != res_b).then_some((assignment, res_a, res_b))
+        })
+        .collect())
+}
+
+// drops the satisfied clauses and the false literals, None if a clause became empty
+fn reduce(
+    clauses: Vec<Vec<(char, bool)>>,
+    values: &[Option<bool>; 26],
+) -> Option<Vec<Vec<(char, bool)>>> {
+    let value = |v: char| values[v as usize - 'A' as usize];
+    let mut reduced = Vec::with_capacity(clauses.len());
+    for clause in clauses {
+        if clause
+            .iter()
+            .any(|&(v, positive)| value(v) == Some(positive))
+        {
+            continue;
+        }
+        let clause: Vec<_> = clause
+            .into_iter()
+            .filter(|&(v, _)| value(v).is_none())
+            .collect();
+        if clause.is_empty() {
+            return None;
+        }
+        reduced.push(clause);
+    }
+    Some(reduced)
+}
+
+// assigns `values` until every clause is satisfied, false if they can't all be
+fn dpll(mut clauses: Vec<Vec<(char, bool)>>, values: &mut [Option<bool>; 26]) -> bool {
+    loop {
+        clauses = match reduce(clauses, values) {
+            Some(clauses) => clauses,
+            None => return false,
+        };
+        let Some(first) = clauses.first() else {
+            return true;
+        };
+        // a unit clause leaves no choice
+        if let Some(&[(v, positive)]) = clauses.iter().map(|c| &c[..]).find(|c| c.len() == 1) {
+            values[v as usize - 'A' as usize] = Some(positive);
+            continue;
+        }
+        // nor does a variable appearing with a single polarity, it can only help
+        let mut polarities = [(false, false); 26];
+        for &(v, positive) in clauses.iter().flatten() {
+            let seen = &mut polarities[v as usize - 'A' as usize];
+            if positive {
+                seen.0 = true;
+            } else {
+                seen.1 = true;
+            }
+        }
+        let pure = polarities
+            .iter()
+            .position(|&(pos, neg)| pos != neg)
+            .map(|i| (i, polarities[i].0));
+        if let Some((i, positive)) = pure {
+            values[i] = Some(positive);
+            continue;
+        }
+        let var = first[0].0 as usize - 'A' as usize;
+        for guess in [true, false] {
+            let mut attempt = *values;
+            attempt[var] = Some(guess);
+            if dpll(clauses.clone(), &mut attempt) {
+                *values = attempt;
+                return true;
+            }
+        }
+        return false;
+    }
+}
+
+pub fn new_variables() -> Vec<VarCell> {
+    ('A'..='Z')
+        .map(|c| {
+            Rc::new(Cell::new(Variable {
+                name: c,
+                value: false,
+            }))
+        })
+        .collect()
+}
+
+impl Tree {
+    // the root's variables must be cells from `variables`
+    pub fn from_root(root: Node, variables: Vec<VarCell>) -> Tree {
+        let mut varlist = Vec::new();
+        root.collect_vars(&mut varlist);
+        varlist.sort_unstable();
+        varlist.dedup();
+        Tree {
+            root,
+            variables,
+            varlist,
+        }
+    }
+
+    // the canonical dnf of the function true on `minterms`, over the first `num_vars` letters
+    // ex: [0, 3] over 2 -> A!B!&AB&|, rows numbered like the truth table, A being the MSB
+    // there are only 26 letters, and every minterm must be below 2^num_vars
+    #[allow(dead_code)]
+    pub fn from_minterms(minterms: &[usize], num_vars: usize) -> Result<Tree, SynthError> {
+        if num_vars > 26 {
+            return Err(SynthError::TooManyVariables {
+                found: num_vars,
+                max: 26,
+            });
+        }
+        let vars: Vec<char> = ('A'..='Z').take(num_vars).collect();
+        let mut minterms = minterms.to_vec();
+        minterms.sort_unstable();
+        minterms.dedup();
+        if let Some(&row) = minterms.iter().find(|&&row| row >> num_vars != 0) {
+            return Err(SynthError::MintermOutOfRange(row));
+        }
+        let variables = new_variables();
+        let root = sum_of_minterms(minterms.into_iter(), &vars, &variables);
+        Ok(Tree::from_root(root, variables))
+    }
+}
+
+impl Tree {
+    // renames every variable through `f`, which must give uppercase letters
+    // variables mapped to the same letter collide and become that one variable, ex: AB& with
+    // every name to A -> AA&, and they all take the value the cell of that letter holds
+    #[allow(dead_code)]
+    pub fn rename_all(&mut self, f: impl Fn(char) -> char) {
+        let root = self.root.rename(&f, &self.variables);
+        *self = Tree::from_root(root, std::mem::take(&mut self.variables));
+    }
+}
+
+// a copy with its own cells, keeping their values: setting a variable of one leaves the other
+// as is, where cloning the Rc would share them
+impl Clone for Tree {
+    fn clone(&self) -> Tree {
+        let variables: Vec<VarCell> = self
+            .variables
+            .iter()
+            .map(|v| Rc::new(Cell::new(v.get())))
+            .collect();
+        Tree {
+            root: self.root.rebind(&variables),
+            variables,
+            varlist: self.varlist.clone(),
+        }
+    }
+}
+
+// a # starts a comment that runs to the end of the line, there are no strings to hold one
+fn strip_comment(line: &str) -> &str {
+    line.split('#').next().unwrap_or_default().trim()
+}
+
+impl std::str::FromStr for Tree {
+    type Err = ParseError;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s: String = s.lines().map(strip_comment).collect();
+        let mut stack = Vec::with_capacity(s.len());
+        let variables: Vec<VarCell> = ('A'..='Z')
+            .map(|c| {
+                Rc::new(Cell::new(Variable {
+                    name: c,
+                    value: false,
+                }))
+            })
+            .collect();
+        let mut varlist = [false; 26];
+
+        for c in s.chars() {
+            match c {
+                '0' | '1' => stack.push(Node::Const(c == '1')),
+                'A'..='Z' => {
+                    let i = c as usize - 'A' as usize;
+                    stack.push(Var(variables[i].clone()));
+                    varlist[i] = true;
+                }
+                '!' => {
+                    let operand = stack.pop().ok_or(MissingOperand)?;
+                    stack.push(Not(Box::new(operand)));
+                }
+                _ => {
+                    let op = c.try_into()?; // BinOp or returns InvalidCharacter
+                    let right = stack.pop().ok_or(MissingOperand)?;
+                    let left = stack.pop().ok_or(MissingOperand)?;
+                    stack.push(Binary {
+                        op,
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    });
+                }
+            }
+        }
+        if stack.len() == 1 {
+            Ok(Tree {
+                root: stack.pop().unwrap(),
+                variables,
+                varlist: varlist
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(i, &v)| {
+                        if v {
+                            Some((i as u8 + b'A') as char)
+                        } else {
+                            None
+                        }
+                    })
+                    .collect(),
+            })
+        } else {
+            Err(UnbalancedExpression)
+        }
+    }
+}
+
+// TODO: implement binary operations for node
+impl std::ops::BitOr for Box<Node> {
+    type Output = Box<Node>;
+    fn bitor(self, other: Box<Node>) -> Box<Node> {
+        Box::new(Binary {
+            op: Or,
+            left: self,
+            right: other,
+        })
+    }
+}
+
+impl std::ops::BitAnd for Box<Node> {
+    type Output = Box<Node>;
+    fn bitand(self, other: Box<Node>) -> Box<Node> {
+        Box::new(Binary {
+            op: And,
+            left: self,
+            right: other,
+        })
+    }
+}
+
+impl std::ops::BitXor for Box<Node> {
+    type Output = Box<Node>;
+    fn bitxor(self, other: Box<Node>) -> Box<Node> {
+        Box::new(Binary {
+            op: Xor,
+            left: self,
+            right: other,
+        })
+    }
+}
+
+// the disjunction of all the nodes, false if there are none
+impl std::iter::Sum for Box<Node> {
+    fn sum<I: Iterator<Item = Box<Node>>>(iter: I) -> Box<Node> {
+        iter.reduce(|left, right| left | right)
+            .unwrap_or_else(|| Box::new(Const(false)))
+    }
+}
+
+// the conjunction of all the nodes, true if there are none
+impl std::iter::Product for Box<Node> {
+    fn product<I: Iterator<Item = Box<Node>>>(iter: I) -> Box<Node> {
+        iter.reduce(|left, right| left & right)
+            .unwrap_or_else(|| Box::new(Const(true)))
+    }
+}
+
+fn leq(left: Box<Node>, right: Box<Node>) -> Box<Node> {
+    Box::new(Binary {
+        op: Leq,
+        left,
+        right,
+    })
+}
+
+// not operator
+impl std::ops::Not for Box<Node> {
+    type Output = Box<Node>;
+    fn not(self) -> Box<Node> {
+        Box::new(Not(self))
+    }
+}
+
+impl std::ops::Not for Node {
+    type Output = Box<Node>;
+    fn not(self) -> Box<Node> {
+        Box::new(Not(Box::new(self)))
+    }
+}
+
+// lets ex06's n-ary trees (e.g. its simplifier's output) feed the functions here
+// this converts whole trees, since variables must point to the new tree's cells
+impl From<ex06_node::Tree> for Tree {
+    fn from(tree: ex06_node::Tree) -> Self {
+        let variables = new_variables();
+        let root = Node::from_ex06(tree.root, &variables);
+        Tree::from_root(root, variables)
+    }
+}
+
+impl Node {
+    fn from_ex06(node: ex06_node::Node, variables: &[VarCell]) -> Node {
+        let mut new = match node.literal {
+            ex06_node::Literal::Const(c) => Const(c),
+            ex06_node::Literal::Var(v) => {
+                Var(variables[v.get().name as usize - 'A' as usize].clone())
+            }
+            ex06_node::Literal::Binary { op, children } => {
+                let op = match op {
+                    ex06_node::BinOp::And => And,
+                    ex06_node::BinOp::Or => Or,
+                    ex06_node::BinOp::Xor => Xor,
+                    ex06_node::BinOp::Impl => Impl,
+                    ex06_node::BinOp::Leq => Leq,
+                };
+                // n-ary children are re-binarized left-associatively: ABC -> (A B) C
+                children
+                    .into_iter()
+                    .map(|child| Node::from_ex06(child, variables))
+                    .reduce(|left, right| Binary {
+                        op,
+                        left: Box::new(left),
+                        right: Box::new(right),
+                    })
+                    .unwrap_or(Const(op == And))
+            }
+        };
+        for _ in 0..node.not {
+            new = Not(Box::new(new));
+        }
+        new
+    }
+
+    // every variable occurrence, left to right, positive unless under an odd number of !
+    // only ! counts, the left side of a > isn't considered negated
+    pub fn literals(&self) -> Vec<Literal> {
+        let mut literals = Vec::new();
+        let mut stack = vec![(self, true)];
+        while let Some((node, positive)) = stack.pop() {
+            match node {
+                Const(_) => (),
+                Var(v) => literals.push((v.get().name, positive)),
+                Not(n) => stack.push((n, !positive)),
+                Binary { left, right, .. } => {
+                    stack.push((right, positive));
+                    stack.push((left, positive));
+                }
+            }
+        }
+        literals
+    }
+
+    // the number of nodes on the longest path from here to a leaf
+    // uses its own stack, it must work on trees too deep to recurse on
+    pub fn depth(&self) -> usize {
+        let mut max = 0;
+        let mut stack = vec![(self, 1)];
+        while let Some((node, depth)) = stack.pop() {
+            max = max.max(depth);
+            match node {
+                Const(_) | Var(_) => (),
+                Not(n) => stack.push((n, depth + 1)),
+                Binary { left, right, .. } => {
+                    stack.push((left, depth + 1));
+                    stack.push((right, depth + 1));
+                }
+            }
+        }
+        max
+    }
+
+    fn collect_vars(&self, vars: &mut Vec<char>) {
+        match self {
+            Const(_) => (),
+            Var(v) => vars.push(v.get().name),
+            Not(n) => n.collect_vars(vars),
+            Binary { left, right, .. } => {
+                left.collect_vars(vars);
+                right.collect_vars(vars);
+            }
+        }
+    }
+}
+
+impl Tree {
+    fn set_var(&self, name: char, value: bool) {
+        self.variables[name as usize - 'A' as usize].set(Variable { name, value });
+    }
+
+    // sets the variables to the assignment of a truth table row, the first variable being the MSB
+    fn set_row(&self, row: usize) {
+        for (j, v) in self.varlist.iter().enumerate() {
+            let j = self.varlist.len() - j - 1;
+            let bit = (row >> j) & 1;
+            self.set_var(*v, bit == 1);
+        }
+    }
+
+    // the variables of the formula, sorted, the order of the truth table columns
+    pub fn varlist(&self) -> &[char] {
+        &self.varlist
+    }
+
+    pub fn satisfy(&self) -> bool {
+        for i in 0..(1 << self.varlist.len()) {
+            self.set_row(i);
+            if self.root.eval() {
+                return true;
+            }
+        }
+        false
+    }
+
+    // evaluates the formula, unless it's nested deeper than the recursion can safely go
+    #[allow(dead_code)]
+    pub fn eval_checked(&self, max_depth: usize) -> Result<bool, DepthError> {
+        let depth = self.root.depth();
+        if depth > max_depth {
+            return Err(DepthError::TooDeep { depth, max_depth });
+        }
+        Ok(self.root.eval())
+    }
+
+    // whether the formula holds under `model`, the variables it doesn't list being false
+    // checks what a solver returns without trusting it
+    #[allow(dead_code)]
+    pub fn verify_model(&self, model: &[(char, bool)]) -> bool {
+        for &v in &self.varlist {
+            self.set_var(v, false);
+        }
+        for &(name, value) in model {
+            if name.is_ascii_uppercase() {
+                self.set_var(name, value);
+            }
+        }
+        self.root.eval()
+    }
+
+    // evaluates with the variables set from the bits of `mask`, the first of varlist being the MSB
+    // the same encoding as the truth table rows
+    #[allow(dead_code)]
+    pub fn eval_mask(&self, mask: u64) -> bool {
+        self.set_row(mask as usize);
+        self.root.eval()
+    }
+
+    // evaluates under the current values, with the rpn and value of every subformula, in post-order
+    // ex: AB& with A=1 B=0 -> (false, [(A, true), (B, false), (AB&, false)])
+    #[allow(dead_code)]
+    pub fn eval_traced(&self) -> (bool, Vec<(String, bool)>) {
+        let mut trace: Vec<(String, bool)> = Vec::new();
+        // the index in the trace of the subformulas not consumed by an operator yet
+        let mut pending = Vec::new();
+        self.root.walk(|step| {
+            let entry = match step {
+                Step::Visit(Var(v)) => (v.get().name.to_string(), v.get().value),
+                Step::Visit(Const(c)) => ((*c as u8).to_string(), *c),
+                Step::Not => {
+                    let (rpn, value) = &trace[pending.pop().unwrap()];
+                    (format!("{}!", rpn), !value)
+                }
+                Step::Op(op) => {
+                    let (right, r) = &trace[pending.pop().unwrap()];
+                    let (left, l) = &trace[pending.pop().unwrap()];
+                    (format!("{}{}{}", left, right, op), op.apply(*l, *r))
+                }
+                Step::Visit(_) => unreachable!("walk only yields leaves"),
+            };
+            pending.push(trace.len());
+            trace.push(entry);
+        });
+        (trace[trace.len() - 1].1, trace)
+    }
+
+    // true under some assignment, the usual name for satisfy
+    // dual of is_valid: valid(f) == !satisfiable(!f)
+    #[allow(dead_code)]
+    pub fn is_satisfiable(&self) -> bool {
+        self.satisfy()
+    }
+
+    // true under every assignment, a tautology
+    // dual of is_satisfiable: satisfiable(f) == !valid(!f)
+    #[allow(dead_code)]
+    pub fn is_valid(&self) -> bool {
+        (0..1 << self.varlist.len()).all(|row| {
+            self.set_row(row);
+            self.root.eval()
+        })
+    }
+
+    // the error of every function building a whole table when there are too many variables
+    fn check_table_size(&self) -> Result<(), TableError> {
+        if self.varlist.len() > MAX_TABLE_VARS {
+            return Err(TableError::TooManyVariables(self.varlist.len()));
+        }
+        Ok(())
+    }
+
+    // up to BITSET_MAX_VARS variables the whole table is one bitsliced evaluation, row i in lane i
+    pub fn truth_table(&self) -> Result<Vec<bool>, TableError> {
+        let n = self.varlist.len();
+        if n > BITSET_MAX_VARS {
+            return self.truth_table_scalar();
+        }
+        let words: HashMap<char, u64> = self
+            .varlist
+            .iter()
+            .enumerate()
+            .map(|(j, &v)| (v, LANE_PATTERNS[n - j - 1]))
+            .collect();
+        let table = self.root.eval_bitsliced(&words);
+        Ok((0..1 << n).map(|row| table >> row & 1 == 1).collect())
+    }
+
+    // the table evaluated row by row, what truth_table does past BITSET_MAX_VARS variables
+    pub fn truth_table_scalar(&self) -> Result<Vec<bool>, TableError> {
+        self.check_table_size()?;
+        Ok((0..(1 << self.varlist.len()))
+            .map(|i| {
+                self.set_row(i);
+                self.root.eval()
+            })
+            .collect())
+    }
+
+    // whether both formulas denote the same function, over the union of their variables
+    // this isn't PartialEq: == reads as structural equality, and this costs a truth table
+    #[allow(dead_code)]
+    pub fn logically_eq(&self, other: &Tree) -> bool {
+        // equal up to the order of commutative operands, no table needed
+        let mut ids = HashMap::new();
+        if self.root.shape_id(&mut ids) == other.root.shape_id(&mut ids) {
+            return true;
+        }
+        let mut vars = self.varlist.clone();
+        vars.extend(&other.varlist);
+        vars.sort_unstable();
+        vars.dedup();
+        (0..1 << vars.len()).all(|row| {
+            for (&name, value) in vars.iter().zip(row_assignment(row, vars.len())) {
+                self.set_var(name, value);
+                other.set_var(name, value);
+            }
+            self.root.eval() == other.root.eval()
+        })
+    }
+
+    // the unminimized product of maxterms, one clause per false row of the truth table
+    // obviously correct, so it serves as a reference for cnf()
+    #[allow(dead_code)]
+    pub fn canonical_cnf(&self) -> Result<Tree, TableError> {
+        let table = self.truth_table()?;
+        let maxterms = (0..table.len()).filter(|&row| !table[row]);
+        let root = product_of_maxterms(maxterms, &self.varlist, &self.variables);
+        Ok(Tree::from_root(root, self.variables.clone()))
+    }
+
+    // the rows of the truth table where the formula is `value`
+    fn rows(&self, value: bool) -> Result<Vec<usize>, TableError> {
+        let table = self.truth_table()?;
+        Ok((0..table.len())
+            .filter(|&row| table[row] == value)
+            .collect())
+    }
+
+    // products of literals, prime implicants, whose or is the formula: the dnf that
+    // formula_from_table builds, term by term, ex: AB| -> [A, B]
+    #[allow(dead_code)]
+    pub fn implicant_cover(&self) -> Result<Vec<String>, TableError> {
+        let n = self.varlist.len();
+        let mut cover = implicant_cover(&self.rows(true)?, n);
+        sort_cover(&mut cover, n);
+        Ok(cover
+            .into_iter()
+            .map(|cube| product_of_cube(cube, &self.varlist, &self.variables).to_string())
+            .collect())
+    }
+
+    // the number of prime implicants of the false rows, the clauses a minimized cnf picks from
+    // ex: the majority of ABC has 3, A|B, A|C and B|C
+    #[allow(dead_code)]
+    pub fn prime_implicant_count(&self) -> Result<usize, TableError> {
+        Ok(prime_implicants(&self.rows(false)?, self.varlist.len()).len())
+    }
+
+    // the clauses made of the prime implicants of the false rows, their literals complemented,
+    // as the merging finds them, to write them out without holding them all, ex: the majority
+    // of ABC -> [(A, true), (B, true)], [(A, true), (C, true)], [(B, true), (C, true)]
+    #[allow(dead_code)]
+    pub fn prime_clauses(&self) -> Result<impl Iterator<Item = Vec<Literal>>, TableError> {
+        let vars = self.varlist.clone();
+        let n = vars.len();
+        let primes = PrimeImplicants::new(&self.rows(false)?, n);
+        Ok(primes.map(move |cube| {
+            vars.iter()
+                .enumerate()
+                .map(|(i, &name)| (1 << (n - i - 1), name))
+                .filter(|&(bit, _)| cube.mask & bit == 0)
+                .map(|(bit, name)| (name, cube.value & bit == 0))
+                .collect()
+        }))
+    }
+
+    // the minimized dnf and cnf, from prime implicants of the true rows and of the false
+    // ones, to keep the smaller, ex: the majority of ABC -> (AB&AC&|BC&|, AB|AC|&BC|&)
+    #[allow(dead_code)]
+    pub fn minimize_both(&self) -> Result<(Tree, Tree), TableError> {
+        let dnf = minimized_dnf(&self.rows(true)?, &self.varlist, &self.variables);
+        let cnf = minimized_cnf(&self.rows(false)?, &self.varlist, &self.variables);
+        Ok((
+            Tree::from_root(dnf, self.variables.clone()),
+            Tree::from_root(cnf, self.variables.clone()),
+        ))
+    }
+
+    // the clauses of the cnf, each literal being a variable and whether it appears positive
+    // ex: AB|C!& -> [[(A, true), (B, true)], [(C, false)]]
+    // a true formula has no clause, a false one only has an empty clause
+    // clauses with a variable and its negation are dropped
+    pub fn cnf_clauses(&self) -> Vec<Vec<(char, bool)>> {
+        fn conjuncts<'a>(node: &'a Node, out: &mut Vec<&'a Node>) {
+            match node {
+                Binary {
+                    op: And,
+                    left,
+                    right,
+                } => {
+                    conjuncts(left, out);
+                    conjuncts(right, out);
+                }
+                _ => out.push(node),
+            }
+        }
+        // whether the clause holds whatever the variables, because of a true constant
+        fn holds(node: &Node) -> bool {
+            match node {
+                Binary {
+                    op: Or,
+                    left,
+                    right,
+                } => holds(left) || holds(right),
+                Const(c) => *c,
+                Var(_) | Not(_) => false,
+                Binary { .. } => unreachable!("cnf only nests ors in ands"),
+            }
+        }
+        let cnf = self.root.clone().cnf();
+        let mut nodes = Vec::new();
+        conjuncts(&cnf, &mut nodes);
+        // the literals of a clause, the variables under the ors, with their polarity
+        let clauses: Vec<Vec<(char, bool)>> = nodes
+            .into_iter()
+            .filter(|node| !holds(node))
+            .map(Node::literals)
+            // a clause with both x and !x always holds
+            .filter(|c| !c.iter().any(|&(v, positive)| c.contains(&(v, !positive))))
+            .collect();
+        // an empty clause makes the whole formula false, the other clauses don't matter
+        if clauses.iter().any(Vec::is_empty) {
+            return vec![vec![]];
+        }
+        clauses
+    }
+
+    // the number of clauses and of literals of the cnf, to compare conversions
+    #[allow(dead_code)]
+    pub fn cnf_stats(&self) -> (usize, usize) {
+        let clauses = self.cnf_clauses();
+        (clauses.len(), clauses.iter().map(Vec::len).sum())
+    }
+
+    // whether no clause of the cnf has more than three literals
+    #[allow(dead_code)]
+    pub fn is_3cnf(&self) -> bool {
+        self.cnf_clauses().iter().all(|clause| clause.len() <= 3)
+    }
+
+    // an equisatisfiable cnf with at most three literals per clause
+    // a | b | c | d | e becomes (a | b | y) & (!y | c | z) & (!z | d | e), one fresh variable
+    // for each literal over three, taken from the letters the formula doesn't use
+    #[allow(dead_code)]
+    pub fn to_3cnf(&self) -> Result<Tree, ThreeCnfError> {
+        let clauses = self.cnf_clauses();
+        let mut free = ('A'..='Z').filter(|v| !self.varlist.contains(v));
+        let needed = clauses.iter().map(|c| c.len().saturating_sub(3)).sum();
+        if needed > 26 - self.varlist.len() {
+            return Err(ThreeCnfError::OutOfVariables {
+                needed,
+                free: 26 - self.varlist.len(),
+            });
+        }
+        let mut cnf = CnfBuilder::new();
+        for clause in clauses {
+            if clause.len() <= 3 {
+                cnf.add_clause(&clause);
+                continue;
+            }
+            let last = clause.len() - 2;
+            let mut previous = clause[0];
+            for &literal in &clause[1..last] {
+                let aux = free.next().expect("counted above");
+                cnf.add_clause(&[previous, literal, (aux, true)]);
+                previous = (aux, false);
+            }
+            cnf.add_clause(&[previous, clause[last], clause[last + 1]]);
+        }
+        Ok(cnf.build())
+    }
+
+    // the edges !a -> b and !b -> a of every clause a | b, a unit clause a being !a -> a
+    // a false formula has an empty clause, which no edge stands for, so it's an error too
+    #[allow(dead_code)]
+    pub fn implication_graph(&self) -> Result<Vec<(Literal, Literal)>, TwoSatError> {
+        let mut edges = Vec::new();
+        for clause in self.cnf_clauses() {
+            match clause[..] {
+                [a] => edges.push(((a.0, !a.1), a)),
+                [a, b] => {
+                    edges.push(((a.0, !a.1), b));
+                    edges.push(((b.0, !b.1), a));
+                }
+                _ => return Err(TwoSatError::ClauseLength(clause.len())),
+            }
+        }
+        Ok(edges)
+    }
+
+    // 2-sat in linear time: unsatisfiable iff a variable and its negation are in the same
+    // strongly connected component of the implication graph, found with kosaraju
+    // the components come out in topological order, a variable is true if its positive
+    // literal comes after the negative one
+    #[allow(dead_code)]
+    pub fn satisfy_2sat(&self) -> Result<Option<Vec<(char, bool)>>, TwoSatError> {
+        fn index((v, positive): Literal) -> usize {
+            (v as usize - 'A' as usize) * 2 + positive as usize
+        }
+        fn order(node: usize, adj: &[Vec<usize>], seen: &mut [bool], out: &mut Vec<usize>) {
+            seen[node] = true;
+            for &next in &adj[node] {
+                if !seen[next] {
+                    order(next, adj, seen, out);
+                }
+            }
+            out.push(node);
+        }
+        fn mark(node: usize, radj: &[Vec<usize>], comp: &mut [Option<usize>], id: usize) {
+            comp[node] = Some(id);
+            for &next in &radj[node] {
+                if comp[next].is_none() {
+                    mark(next, radj, comp, id);
+                }
+            }
+        }
+        let edges = match self.implication_graph() {
+            Ok(edges) => edges,
+            Err(TwoSatError::ClauseLength(0)) => return Ok(None),
+            Err(e) => return Err(e),
+        };
+        let mut adj = vec![Vec::new(); 52];
+        let mut radj = vec![Vec::new(); 52];
+        for (from, to) in edges {
+            adj[index(from)].push(index(to));
+            radj[index(to)].push(index(from));
+        }
+        let mut seen = [false; 52];
+        let mut finished = Vec::with_capacity(52);
+        for node in 0..52 {
+            if !seen[node] {
+                order(node, &adj, &mut seen, &mut finished);
+            }
+        }
+        let mut comp = [None; 52];
+        let mut id = 0;
+        for &node in finished.iter().rev() {
+            if comp[node].is_none() {
+                mark(node, &radj, &mut comp, id);
+                id += 1;
+            }
+        }
+        let mut model = Vec::with_capacity(self.varlist.len());
+        for &v in &self.varlist {
+            let (positive, negative) = (comp[index((v, true))], comp[index((v, false))]);
+            if positive == negative {
+                return Ok(None);
+            }
+            model.push((v, positive > negative));
+        }
+        Ok(Some(model))
+    }
+
+    // whether every clause of the cnf has at most one positive literal
+    // those formulas can be solved in linear time
+    #[allow(dead_code)]
+    pub fn is_horn(&self) -> bool {
+        self.cnf_clauses()
+            .iter()
+            .all(|clause| clause.iter().filter(|(_, positive)| *positive).count() <= 1)
+    }
+
+    // a model of the formula, or None if it's unsatisfiable
+    // horn formulas get the linear marking algorithm, others fall back to the truth table
+    #[allow(dead_code)]
+    pub fn satisfy_horn(&self) -> Option<Vec<(char, bool)>> {
+        if !self.is_horn() {
+            return (0..1 << self.varlist.len())
+                .find(|&row| {
+                    self.set_row(row);
+                    self.root.eval()
+                })
+                .map(|row| {
+                    let values = row_assignment(row, self.varlist.len());
+                    self.varlist.iter().copied().zip(values).collect()
+                });
+        }
+        let clauses = self.cnf_clauses();
+        let index = |v: char| v as usize - 'A' as usize;
+        // the number of negative literals not yet made false, for each clause
+        let mut pending: Vec<usize> = clauses
+            .iter()
+            .map(|clause| clause.iter().filter(|(_, positive)| !positive).count())
+            .collect();
+        // the clauses where each variable appears negated
+        let mut watchers = vec![Vec::new(); 26];
+        for (i, clause) in clauses.iter().enumerate() {
+            for &(v, positive) in clause {
+                if !positive {
+                    watchers[index(v)].push(i);
+                }
+            }
+        }
+        // everything starts false, and only what's forced becomes true
+        let mut values = [false; 26];
+        let mut queue: Vec<usize> = (0..clauses.len()).filter(|&i| pending[i] == 0).collect();
+        while let Some(i) = queue.pop() {
+            match clauses[i].iter().find(|(_, positive)| *positive) {
+                None => return None,
+                Some(&(v, _)) if !values[index(v)] => {
+                    values[index(v)] = true;
+                    for &j in &watchers[index(v)] {
+                        pending[j] -= 1;
+                        if pending[j] == 0 {
+                            queue.push(j);
+                        }
+                    }
+                }
+                Some(_) => (),
+            }
+        }
+        Some(
+            self.varlist
+                .iter()
+                .map(|&v| (v, values[index(v)]))
+                .collect(),
+        )
+    }
 
-impl From<BinOp> for char {
-    fn from(op: BinOp) -> Self {
-        match op {
-            And => '&',
-            Or => '|',
-            Xor => '^',
-            Impl => '>',
-            Leq => '=',
+    // a model of the formula, or None if it's unsatisfiable, searched with DPLL on its cnf
+    // the clauses are the literals() of the cnf's clauses, pure literals are read from them
+    // variables the search didn't need to assign are false
+    #[allow(dead_code)]
+    pub fn satisfy_dpll(&self) -> Option<Vec<(char, bool)>> {
+        let mut values = [None; 26];
+        if !dpll(self.cnf_clauses(), &mut values) {
+            return None;
         }
+        let model = self
+            .varlist
+            .iter()
+            .map(|&v| (v, values[v as usize - 'A' as usize]));
+        Some(
+            model
+                .map(|(v, value)| (v, value.unwrap_or(false)))
+                .collect(),
+        )
     }
-}
 
-impl fmt::Display for BinOp {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "{}", char::from(*self))
+    // the formula with the fewest symbols computing the same function
+    // every formula is tried, by size, so this is exact but only for tiny formulas: None past
+    // SHORTEST_MAX_VARS variables
+    #[allow(dead_code)]
+    pub fn shortest_equivalent(&self) -> Option<Tree> {
+        let n = self.varlist.len();
+        if n > SHORTEST_MAX_VARS {
+            return None;
+        }
+        // functions as the bits of their tables, row 0 being the LSB
+        let rows = 1 << n;
+        let mask: u64 = if rows == 64 { !0 } else { (1 << rows) - 1 };
+        let target = self
+            .truth_table()
+            .expect("a few variables fit a table")
+            .iter()
+            .enumerate()
+            .fold(0u64, |acc, (i, &res)| acc | (res as u64) << i);
+        // the first formula found for each function, they are found by size
+        let mut best: HashMap<u64, String> = HashMap::new();
+        // the functions first reached at each size
+        let mut levels: Vec<Vec<u64>> = vec![Vec::new()];
+        for size in 1..=SHORTEST_MAX_LEN {
+            let mut found: Vec<(u64, String)> = Vec::new();
+            if size == 1 {
+                for (j, &v) in self.varlist.iter().enumerate() {
+                    let table = (0..rows)
+                        .filter(|row| (row >> (n - j - 1)) & 1 == 1)
+                        .fold(0u64, |acc, row| acc | 1 << row);
+                    found.push((table, v.to_string()));
+                }
+                found.push((0, "0".to_string()));
+                found.push((mask, "1".to_string()));
+            } else {
+                for &t in &levels[size - 1] {
+                    found.push((!t & mask, format!("{}!", best[&t])));
+                }
+                for left_size in 1..size - 1 {
+                    let right_size = size - 1 - left_size;
+                    for &l in &levels[left_size] {
+                        for &r in &levels[right_size] {
+                            for (op, table) in [
+                                (And, l & r),
+                                (Or, l | r),
+                                (Xor, l ^ r),
+                                (Impl, (!l | r) & mask),
+                                (Leq, !(l ^ r) & mask),
+                            ] {
+                                found.push((table, format!("{}{}{}", best[&l], best[&r], op)));
+                            }
+                        }
+                    }
+                }
+            }
+            let mut level = Vec::new();
+            for (table, formula) in found {
+                if let std::collections::hash_map::Entry::Vacant(e) = best.entry(table) {
+                    e.insert(formula);
+                    level.push(table);
+                }
+            }
+            levels.push(level);
+            if let Some(formula) = best.get(&target) {
+                return Some(
+                    formula
+                        .parse()
+                        .expect("the search only builds valid formulas"),
+                );
+            }
+        }
+        // the None above is what keeps this out of reach
+        unreachable!(
+            "every function of up to {} variables has a formula of at most {} symbols",
+            SHORTEST_MAX_VARS, SHORTEST_MAX_LEN
+        )
     }
-}
 
-impl fmt::Display for Node {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            Binary { op, left, right } => write!(f, "{}{}{}", left, right, op),
-            Not(operand) => write!(f, "{}!", operand),
-            Var(val) => write!(f, "{}", val.get().name),
-            Const(val) => write!(f, "{}", *val as u8),
+    // the whole function as a lookup table, keyed by assignments in varlist order
+    #[allow(dead_code)]
+    pub fn eval_all(&self) -> Result<HashMap<Vec<bool>, bool>, TableError> {
+        let n = self.varlist.len();
+        Ok(self
+            .truth_table()?
+            .into_iter()
+            .enumerate()
+            .map(|(row, res)| (row_assignment(row, n), res))
+            .collect())
+    }
+
+    // the Walsh-Hadamard transform of the table, as +1 for false and -1 for true
+    // entry w is the correlation with the xor of the variables selected by w's bits
+    #[allow(dead_code)]
+    pub fn walsh_spectrum(&self) -> Result<Vec<i32>, TableError> {
+        let mut spectrum: Vec<i32> = self
+            .truth_table()?
+            .into_iter()
+            .map(|res| if res { -1 } else { 1 })
+            .collect();
+        // in place butterflies, one round per variable
+        let mut half = 1;
+        while half < spectrum.len() {
+            for block in (0..spectrum.len()).step_by(2 * half) {
+                for i in block..block + half {
+                    let (a, b) = (spectrum[i], spectrum[i + half]);
+                    spectrum[i] = a + b;
+                    spectrum[i + half] = a - b;
+                }
+            }
+            half *= 2;
         }
+        Ok(spectrum)
     }
-}
 
-impl fmt::Debug for ParseError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            MissingOperand => write!(f, "Missing operand"),
-            InvalidCharacter(c) => write!(f, "Invalid character: '{}'", c),
-            UnbalancedExpression => write!(f, "Unbalanced expression"),
+    // the truth table as bits, bit i set when row i is true
+    // f implies g exactly when f's set is a subset of g's, over the same variables
+    #[allow(dead_code)]
+    pub fn satisfying_set(&self) -> Result<u64, BitsetError> {
+        if self.varlist.len() > BITSET_MAX_VARS {
+            return Err(BitsetError::TooManyVariables(self.varlist.len()));
         }
+        Ok(self
+            .truth_table()
+            .expect("a u64 of rows fits a table")
+            .iter()
+            .enumerate()
+            .fold(0, |set, (row, &res)| set | (res as u64) << row))
     }
-}
 
-impl std::str::FromStr for Tree {
-    type Err = ParseError;
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        let mut stack = Vec::with_capacity(s.len());
-        let variables: Vec<VarCell> = ('A'..='Z')
-            .map(|c| {
-                Rc::new(Cell::new(Variable {
-                    name: c,
-                    value: false,
-                }))
+    pub fn count_models(&self) -> Result<usize, TableError> {
+        Ok(truth_table_weight(&self.truth_table()?))
+    }
+
+    // how often the formula is true: (true rows, rows), ex: AB| -> (3, 4)
+    #[allow(dead_code)]
+    pub fn truth_density(&self) -> Result<(usize, usize), TableError> {
+        Ok((self.count_models()?, 1 << self.varlist.len()))
+    }
+
+    // the variables the result actually depends on, ex: AB&A| -> [A]
+    pub fn essential_variables(&self) -> Result<Vec<char>, TableError> {
+        let table = self.truth_table()?;
+        let n = self.varlist.len();
+        Ok(self
+            .varlist
+            .iter()
+            .enumerate()
+            .filter(|(j, _)| {
+                let bit = 1 << (n - j - 1);
+                (0..table.len()).any(|i| table[i] != table[i ^ bit])
             })
-            .collect();
-        let mut varlist = [false; 26];
+            .map(|(_, &v)| v)
+            .collect())
+    }
 
-        for c in s.chars() {
-            match c {
-                '0' | '1' => stack.push(Node::Const(c == '1')),
-                'A'..='Z' => {
-                    let i = c as usize - 'A' as usize;
-                    stack.push(Var(variables[i].clone()));
-                    varlist[i] = true;
-                }
-                '!' => {
-                    let operand = stack.pop().ok_or(MissingOperand)?;
-                    stack.push(Not(Box::new(operand)));
-                }
-                _ => {
-                    let op = c.try_into()?; // BinOp or returns InvalidCharacter
-                    let right = stack.pop().ok_or(MissingOperand)?;
-                    let left = stack.pop().ok_or(MissingOperand)?;
-                    stack.push(Binary {
-                        op,
-                        left: Box::new(left),
-                        right: Box::new(right),
-                    });
-                }
-            }
-        }
-        if stack.len() == 1 {
-            Ok(Tree {
-                root: stack.pop().unwrap(),
-                variables,
-                varlist: varlist
-                    .iter()
-                    .enumerate()
-                    .filter_map(|(i, &v)| {
-                        if v {
-                            Some((i as u8 + b'A') as char)
-                        } else {
-                            None
-                        }
+    // for each variable, the fraction of the rows where flipping it flips the result
+    // the weight of the boolean derivative over the size of the table, ex: AB& -> A 0.5, B 0.5
+    #[allow(dead_code)]
+    pub fn influences(&self) -> Vec<(char, f64)> {
+        let rows = 1usize << self.varlist.len();
+        self.varlist
+            .iter()
+            .map(|&v| {
+                let derivative = self.root.boolean_derivative(v);
+                let table: Vec<bool> = (0..rows)
+                    .map(|row| {
+                        self.set_row(row);
+                        derivative.eval()
                     })
-                    .collect(),
+                    .collect();
+                (v, truth_table_weight(&table) as f64 / rows as f64)
             })
-        } else {
-            Err(UnbalancedExpression)
-        }
+            .collect()
     }
-}
 
-// TODO: implement binary operations for node
-impl std::ops::BitOr for Box<Node> {
-    type Output = Box<Node>;
-    fn bitor(self, other: Box<Node>) -> Box<Node> {
-        Box::new(Binary {
-            op: Or,
-            left: self,
-            right: other,
+    // a variable order for a decision diagram: the most influential first, ties going to the
+    // first to appear in the formula, the variables it doesn't depend on last
+    // only a heuristic, the smallest diagram can need another order
+    #[allow(dead_code)]
+    pub fn suggest_var_order(&self) -> Vec<char> {
+        let mut appearance = Vec::new();
+        self.root.collect_vars(&mut appearance);
+        let first = |v: char| appearance.iter().position(|&a| a == v);
+        let mut order = self.influences();
+        order.sort_by(|a, b| b.1.total_cmp(&a.1).then(first(a.0).cmp(&first(b.0))));
+        order.into_iter().map(|(v, _)| v).collect()
+    }
+
+    // whether raising `var` can only raise the result: f(var=0) <= f(var=1) everywhere
+    #[allow(dead_code)]
+    pub fn is_positive_unate(&self, var: char) -> bool {
+        self.cofactors_all(var, |negative, positive| !negative || positive)
+    }
+
+    // whether raising `var` can only lower the result: f(var=0) >= f(var=1) everywhere
+    #[allow(dead_code)]
+    pub fn is_negative_unate(&self, var: char) -> bool {
+        self.cofactors_all(var, |negative, positive| negative || !positive)
+    }
+
+    // whether swapping the two variables leaves the function unchanged
+    // only the rows where they differ can change, so f(a=0, b=1) must equal f(a=1, b=0)
+    #[allow(dead_code)]
+    pub fn symmetric_in(&self, a: char, b: char) -> bool {
+        if a == b {
+            return true;
+        }
+        let a0_b1 = self.root.cofactor(a, false).cofactor(b, true);
+        let a1_b0 = self.root.cofactor(a, true).cofactor(b, false);
+        (0..1 << self.varlist.len()).all(|row| {
+            self.set_row(row);
+            a0_b1.eval() == a1_b0.eval()
         })
     }
-}
 
-impl std::ops::BitAnd for Box<Node> {
-    type Output = Box<Node>;
-    fn bitand(self, other: Box<Node>) -> Box<Node> {
-        Box::new(Binary {
-            op: And,
-            left: self,
-            right: other,
+    // the algebraic normal form: which ands of variables the function xors together
+    // indexed like the truth table, ex: AB^ -> [0, 1, 1, 0], B ^ A with no constant nor AB
+    #[allow(dead_code)]
+    pub fn anf(&self) -> Result<Vec<bool>, TableError> {
+        let mut coefficients = self.truth_table()?;
+        let mut bit = 1;
+        while bit < coefficients.len() {
+            for i in 0..coefficients.len() {
+                if i & bit != 0 {
+                    coefficients[i] ^= coefficients[i ^ bit];
+                }
+            }
+            bit <<= 1;
+        }
+        Ok(coefficients)
+    }
+
+    // a xor of variables and maybe 1, no and in the anf
+    #[allow(dead_code)]
+    pub fn is_affine(&self) -> Result<bool, TableError> {
+        Ok(self
+            .anf()?
+            .iter()
+            .enumerate()
+            .all(|(monomial, &c)| !c || monomial.count_ones() <= 1))
+    }
+
+    #[allow(dead_code)]
+    pub fn classify(&self) -> Result<FunctionClass, TableError> {
+        Ok(match self.essential_variables()?.len() {
+            0 => FunctionClass::Constant,
+            1 => FunctionClass::Literal,
+            _ if self.is_affine()? => FunctionClass::Affine,
+            _ if self.varlist.iter().all(|&v| self.is_positive_unate(v)) => FunctionClass::Monotone,
+            _ => FunctionClass::General,
         })
     }
-}
 
-impl std::ops::BitXor for Box<Node> {
-    type Output = Box<Node>;
-    fn bitxor(self, other: Box<Node>) -> Box<Node> {
-        Box::new(Binary {
-            op: Xor,
-            left: self,
-            right: other,
+    // checks `pred` on both cofactors' values, for every row of the truth table
+    fn cofactors_all(&self, var: char, pred: impl Fn(bool, bool) -> bool) -> bool {
+        let negative = self.root.cofactor(var, false);
+        let positive = self.root.cofactor(var, true);
+        (0..1 << self.varlist.len()).all(|row| {
+            self.set_row(row);
+            pred(negative.eval(), positive.eval())
         })
     }
-}
 
-fn leq(left: Box<Node>, right: Box<Node>) -> Box<Node> {
-    Box::new(Binary {
-        op: Leq,
-        left,
-        right,
-    })
-}
+    // counts the models over the essential variables only, ignoring the redundant ones
+    #[allow(dead_code)]
+    pub fn core_model_count(&self) -> Result<usize, TableError> {
+        let redundant = self.varlist.len() - self.essential_variables()?.len();
+        // each assignment of the essential variables is repeated for every redundant one
+        Ok(self.count_models()? >> redundant)
+    }
 
-// not operator
-impl std::ops::Not for Box<Node> {
-    type Output = Box<Node>;
-    fn not(self) -> Box<Node> {
-        Box::new(Not(self))
+    // the variables in the order they first appear in the rpn, ex: BA& -> [B, A]
+    #[allow(dead_code)]
+    pub fn variables_first_seen(&self) -> Vec<char> {
+        let mut all = Vec::new();
+        self.root.collect_vars(&mut all);
+        let mut vars = Vec::new();
+        for v in all {
+            if !vars.contains(&v) {
+                vars.push(v);
+            }
+        }
+        vars
     }
-}
 
-impl std::ops::Not for Node {
-    type Output = Box<Node>;
-    fn not(self) -> Box<Node> {
-        Box::new(Not(Box::new(self)))
+    // evaluates the formula with the current values, toggling a variable afterwards
+    // only re-evaluates the nodes between it and the root
+    #[allow(dead_code)]
+    pub fn eval_cached(&self) -> CachedEval {
+        CachedEval::new(&self.root, &self.variables)
+    }
+
+    // dumps the AST as json, ex: {"op":"and","left":{"var":"A"},"right":{"not":{"var":"B"}}}
+    #[allow(dead_code)]
+    pub fn to_json(&self) -> String {
+        self.root.fold(
+            |c| format!("{{\"const\":{}}}", c),
+            |v| format!("{{\"var\":\"{}\"}}", v),
+            |n| format!("{{\"not\":{}}}", n),
+            |op, l, r| {
+                let op = match op {
+                    And => "and",
+                    Or => "or",
+                    Xor => "xor",
+                    Impl => "impl",
+                    Leq => "leq",
+                };
+                format!("{{\"op\":\"{}\",\"left\":{},\"right\":{}}}", op, l, r)
+            },
+        )
     }
 }
 
-impl Tree {
-    fn set_var(&self, name: char, value: bool) {
-        self.variables[name as usize - 'A' as usize].set(Variable { name, value });
+impl Node {
+    // iterative, for trees too deep to recurse on, and the right operand of & | > is skipped
+    // when the left one decides
+    pub fn eval(&self) -> bool {
+        // what is left to do with a value on the way up
+        enum Then<'a> {
+            Not,
+            Right(BinOp, &'a Node),
+            Apply(BinOp, bool),
+        }
+        let mut pending = Vec::new();
+        let mut node = self;
+        loop {
+            // down to the leftmost leaf
+            let mut value = loop {
+                match node {
+                    Const(c) => break *c,
+                    Var(v) => break v.get().value,
+                    Not(operand) => {
+                        pending.push(Then::Not);
+                        node = operand;
+                    }
+                    Binary { op, left, right } => {
+                        pending.push(Then::Right(*op, right));
+                        node = left;
+                    }
+                }
+            };
+            // and back up, until a right operand is needed
+            loop {
+                match pending.pop() {
+                    None => return value,
+                    Some(Then::Not) => value = !value,
+                    Some(Then::Apply(op, left)) => value = op.apply(left, value),
+                    Some(Then::Right(And, _)) if !value => (),
+                    Some(Then::Right(Or, _)) if value => (),
+                    Some(Then::Right(Impl, _)) if !value => value = true,
+                    Some(Then::Right(op, right)) => {
+                        pending.push(Then::Apply(op, value));
+                        node = right;
+                        break;
+                    }
+                }
+            }
+        }
     }
 
-    pub fn satisfy(&self) -> bool {
-        for i in 0..(1 << self.varlist.len()) {
-            for (j, v) in self.varlist.iter().enumerate() {
-                let j = self.varlist.len() - j - 1;
-                let bit = (i >> j) & 1;
-                self.set_var(*v, bit == 1);
+    // evaluates 64 assignments at once, bit i of each word being the value in assignment i
+    // a variable missing from `var_words` is false in all of them
+    pub fn eval_bitsliced(&self, var_words: &HashMap<char, u64>) -> u64 {
+        let mut words = Vec::new();
+        self.walk(|step| match step {
+            Step::Visit(Var(v)) => words.push(var_words.get(&v.get().name).copied().unwrap_or(0)),
+            Step::Visit(Const(c)) => words.push(if *c { u64::MAX } else { 0 }),
+            Step::Not => {
+                let operand = words.pop().unwrap();
+                words.push(!operand);
             }
-            if self.root.eval() {
-                return true;
+            Step::Op(op) => {
+                let right = words.pop().unwrap();
+                let left = words.pop().unwrap();
+                words.push(match op {
+                    And => left & right,
+                    Or => left | right,
+                    Impl => !left | right,
+                    Leq => !(left ^ right),
+                    Xor => left ^ right,
+                });
+            }
+            Step::Visit(_) => unreachable!("walk only yields leaves"),
+        });
+        words[0]
+    }
+
+    // folds the tree bottom-up, so analyses don't have to hand-roll the recursion
+    // over walk, with the values of the operands on a stack, so deep trees don't overflow
+    pub fn fold<T>(
+        &self,
+        on_const: impl Fn(bool) -> T,
+        on_var: impl Fn(char) -> T,
+        on_not: impl Fn(T) -> T,
+        on_binary: impl Fn(BinOp, T, T) -> T,
+    ) -> T {
+        let mut values = Vec::new();
+        self.walk(|step| {
+            let value = match step {
+                Step::Visit(Const(c)) => on_const(*c),
+                Step::Visit(Var(v)) => on_var(v.get().name),
+                Step::Not => on_not(values.pop().unwrap()),
+                Step::Op(op) => {
+                    let right = values.pop().unwrap();
+                    let left = values.pop().unwrap();
+                    on_binary(*op, left, right)
+                }
+                Step::Visit(_) => unreachable!("walk only yields leaves"),
+            };
+            values.push(value);
+        });
+        values.pop().unwrap()
+    }
+
+    // the same formula pointing at the cells of `variables`
+    fn rebind(&self, variables: &[VarCell]) -> Node {
+        self.rename(&|name| name, variables)
+    }
+
+    // the formula with every variable renamed by `f`, pointing at the cells of `variables`
+    fn rename(&self, f: &impl Fn(char) -> char, variables: &[VarCell]) -> Node {
+        match self {
+            Const(c) => Const(*c),
+            Var(v) => {
+                let name = f(v.get().name);
+                assert!(name.is_ascii_uppercase(), "{:?} is not a variable", name);
+                Var(variables[name as usize - 'A' as usize].clone())
             }
+            Not(n) => Not(Box::new(n.rename(f, variables))),
+            Binary { op, left, right } => Binary {
+                op: *op,
+                left: Box::new(left.rename(f, variables)),
+                right: Box::new(right.rename(f, variables)),
+            },
         }
-        false
     }
-}
 
-impl Node {
-    pub fn eval(&self) -> bool {
+    // the formula with `var` fixed to `value`, ex: AB& with A=1 -> 1B&
+    pub fn cofactor(&self, var: char, value: bool) -> Node {
         match self {
-            Const(c) => *c,
-            Var(v) => v.get().value,
-            Not(n) => !n.eval(),
-            Binary { op, left, right } => match op {
-                And => left.eval() && right.eval(),
-                Or => left.eval() || right.eval(),
-                Impl => !left.eval() || right.eval(),
-                Leq => left.eval() == right.eval(),
-                Xor => left.eval() ^ right.eval(),
+            Var(v) if v.get().name == var => Const(value),
+            Const(_) | Var(_) => self.clone(),
+            Not(n) => Not(Box::new(n.cofactor(var, value))),
+            Binary { op, left, right } => Binary {
+                op: *op,
+                left: Box::new(left.cofactor(var, value)),
+                right: Box::new(right.cofactor(var, value)),
             },
         }
     }
 
-    pub fn cnf(self) -> Box<Node> {
+    // the boolean difference, true wherever flipping `var` flips the result
+    pub fn boolean_derivative(&self, var: char) -> Node {
+        let positive = Box::new(self.cofactor(var, true));
+        let negative = Box::new(self.cofactor(var, false));
+        *(positive ^ negative)
+    }
+
+    // the rpn of every largest subtree that doesn't depend on its variables, ex: AA^B| -> [AA^]
+    // single variables and constants aren't reported, they're not a mistake
+    pub fn constant_subtrees(&self) -> Vec<String> {
+        let mut found = Vec::new();
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            match node {
+                Const(_) | Var(_) => (),
+                _ if node.is_constant() => found.push(node.to_string()),
+                Not(n) => stack.push(n),
+                Binary { left, right, .. } => {
+                    stack.push(right);
+                    stack.push(left);
+                }
+            }
+        }
+        found
+    }
+
+    // same result on every row of its own truth table
+    // the variable cells are shared, this overwrites the values of the whole tree
+    fn is_constant(&self) -> bool {
+        let mut cells: Vec<&VarCell> = Vec::new();
+        let mut stack = vec![self];
+        while let Some(node) = stack.pop() {
+            match node {
+                Const(_) => (),
+                Var(v) if cells.iter().any(|c| Rc::ptr_eq(c, v)) => (),
+                Var(v) => cells.push(v),
+                Not(n) => stack.push(n),
+                Binary { left, right, .. } => {
+                    stack.push(left);
+                    stack.push(right);
+                }
+            }
+        }
+        let mut results = (0..1usize << cells.len()).map(|row| {
+            for (j, cell) in cells.iter().enumerate() {
+                let name = cell.get().name;
+                let value = (row >> j) & 1 == 1;
+                cell.set(Variable { name, value });
+            }
+            self.eval()
+        });
+        let first = results.next();
+        results.all(|r| Some(r) == first)
+    }
+
+    // turns the node into its negation in nnf: only & and | over variables maybe under a !
+    // > ^ and = are expanded on the way, ^ and = repeating their operands, ex: AB> -> AB!&
+    pub fn negate(&mut self) {
+        self.push_not(true);
+    }
+
+    // rewrites the node in nnf, negated or not
+    fn push_not(&mut self, negate: bool) {
         match self {
-            Const(val) => Box::new(Const(val)),
-            Var(v) => Box::new(Var(v)),
+            Const(c) => *c ^= negate,
+            Var(_) if negate => {
+                let var = std::mem::replace(self, Const(false));
+                *self = Not(Box::new(var));
+            }
+            Var(_) => (),
+            Not(n) => {
+                let operand = std::mem::replace(&mut **n, Const(false));
+                *self = operand;
+                self.push_not(!negate);
+            }
             Binary { op, left, right } => match op {
+                // !(A & B) -> !A | !B
+                And | Or => {
+                    if negate {
+                        *op = if *op == And { Or } else { And };
+                    }
+                    left.push_not(negate);
+                    right.push_not(negate);
+                }
+                // A > B -> !A | B, !(A > B) -> A & !B
+                Impl => {
+                    *op = if negate { And } else { Or };
+                    left.push_not(!negate);
+                    right.push_not(negate);
+                }
+                // A ^ B -> A & !B | !A & B, A = B -> A & B | !A & !B, ! swapping the two
+                Xor | Leq => {
+                    let xor = (*op == Xor) != negate;
+                    let term = |left_negated: bool, right_negated: bool| {
+                        let (mut left, mut right) = (left.clone(), right.clone());
+                        left.push_not(left_negated);
+                        right.push_not(right_negated);
+                        left & right
+                    };
+                    let either = term(false, xor) | term(true, !xor);
+                    *self = *either;
+                }
+            },
+        }
+    }
+
+    // swaps & and |, 0 and 1, the other operators are expanded to & | ! first
+    pub fn dual(&self) -> Node {
+        let node = match self {
+            Const(c) => return Const(!c),
+            Var(_) => return self.clone(),
+            Not(n) => return Not(Box::new(n.dual())),
+            Binary { op, left, right } => {
+                let left = Box::new(left.dual());
+                let right = Box::new(right.dual());
+                match op {
+                    And => left | right,
+                    Or => left & right,
+                    // A > B -> !A | B
+                    Impl => !left & right,
+                    // A ^ B -> (A & !B) | (!A & B)
+                    Xor => (left.clone() | !right.clone()) & (!left | right),
+                    // A = B -> (A & B) | (!A & !B)
+                    Leq => (left.clone() | right.clone()) & (!left | !right),
+                }
+            }
+        };
+        *node
+    }
+
+    // the tree drawn with one node per line, the operands under their operator, ex: AB&C| ->
+    // |
+    // ├── &
+    // │   ├── A
+    // │   └── B
+    // └── C
+    #[allow(dead_code)]
+    pub fn to_tree_string(&self) -> String {
+        let mut out = String::new();
+        self.write_tree(&mut out, "", "");
+        out
+    }
+
+    // `first` goes before this node's own line, `rest` before the lines of its operands
+    fn write_tree(&self, out: &mut String, first: &str, rest: &str) {
+        let (label, operands) = match self {
+            Const(c) => ((*c as u8).to_string(), vec![]),
+            Var(v) => (v.get().name.to_string(), vec![]),
+            Not(n) => ("!".to_string(), vec![&**n]),
+            Binary { op, left, right } => (op.to_string(), vec![&**left, &**right]),
+        };
+        out.push_str(first);
+        out.push_str(&label);
+        out.push('\n');
+        for (i, operand) in operands.iter().enumerate() {
+            if i + 1 == operands.len() {
+                operand.write_tree(out, &format!("{}└── ", rest), &format!("{}    ", rest));
+            } else {
+                operand.write_tree(out, &format!("{}├── ", rest), &format!("{}│   ", rest));
+            }
+        }
+    }
+
+    // the operands of &, |, ^ and = sorted, > keeps its order
+    // formulas equal up to commutativity come out identical, ex: BA= and AB= -> AB=
+    pub fn canonicalize(&self) -> Node {
+        match self {
+            Const(_) | Var(_) => self.clone(),
+            Not(n) => Not(Box::new(n.canonicalize())),
+            Binary { op, left, right } => {
+                let mut left = Box::new(left.canonicalize());
+                let mut right = Box::new(right.canonicalize());
+                if *op != Impl && left > right {
+                    std::mem::swap(&mut left, &mut right);
+                }
+                Binary {
+                    op: *op,
+                    left,
+                    right,
+                }
+            }
+        }
+    }
+
+    // fixes the assigned variables and simplifies, the others stay free
+    pub fn restrict(&self, partial: &HashMap<char, bool>) -> Node {
+        let restricted = partial.iter().fold(self.clone(), |node, (&var, &value)| {
+            node.cofactor(var, value)
+        });
+        *restricted.simplify()
+    }
+
+    pub fn cnf(self) -> Box<Node> {
+        match self.into_parts() {
+            NodeParts::Const(val) => Box::new(Const(val)),
+            NodeParts::Var(v) => Box::new(Var(v)),
+            NodeParts::Binary { op, left, right } => match op {
                 // Xor -> (A | B) & (!A | !B)
                 Xor => ((left.clone() | right.clone()) & (!left | !right)).cnf(),
                 // Impl -> !A | B
@@ -270,33 +2517,41 @@ impl Node {
                     // recurse first to bring up any ANDs
                     let left = left.cnf();
                     let right = right.cnf();
-                    if let Binary {
-                        op: And,
-                        left: ll,
-                        right: lr,
-                    } = *left
-                    {
-                        // (A & B) | C -> (A | C) & (B | C)
-                        ((ll | right.clone()) & (lr | right)).cnf()
-                    } else if let Binary {
-                        op: And,
-                        left: rl,
-                        right: rr,
-                    } = *right
-                    {
-                        // A & (B | C) -> (A | B) & (A | C)
-                        ((left.clone() | rl) & (left | rr)).cnf()
-                    } else {
+                    match (left.into_parts(), right.into_parts()) {
+                        (
+                            NodeParts::Binary {
+                                op: And,
+                                left: ll,
+                                right: lr,
+                            },
+                            right,
+                        ) => {
+                            // (A & B) | C -> (A | C) & (B | C)
+                            let right = Box::new(Node::from(right));
+                            ((ll | right.clone()) & (lr | right)).cnf()
+                        }
+                        (
+                            left,
+                            NodeParts::Binary {
+                                op: And,
+                                left: rl,
+                                right: rr,
+                            },
+                        ) => {
+                            // A & (B | C) -> (A | B) & (A | C)
+                            let left = Box::new(Node::from(left));
+                            ((left.clone() | rl) & (left | rr)).cnf()
+                        }
                         // if neither left nor right is an And, we're done
-                        left | right
+                        (left, right) => Box::new(Node::from(left)) | Box::new(Node::from(right)),
                     }
                 }
             },
-            Not(operand) => match *operand {
-                Const(val) => Box::new(Const(!val)),
-                Var(v) => !Var(v),
-                Not(operand) => (*operand).cnf(),
-                Binary { op, left, right } => match op {
+            NodeParts::Not(operand) => match operand.into_parts() {
+                NodeParts::Const(val) => Box::new(Const(!val)),
+                NodeParts::Var(v) => !Var(v),
+                NodeParts::Not(operand) => operand.cnf(),
+                NodeParts::Binary { op, left, right } => match op {
                     // !(A & B) -> !A | !B
                     And => (!left | !right).cnf(),
                     // !(A | B) -> !A & !B
@@ -340,99 +2595,106 @@ impl Node {
     }
 
     pub fn simplify(self) -> Box<Node> {
-        match self {
-            Const(val) => Box::new(Const(val)),
-            Var(v) => Box::new(Var(v)),
-            Not(n) => match *n {
-                Const(val) => Box::new(Const(!val)),
-                Var(v) => !Var(v),
-                Not(n) => (*n).simplify(),
-                Binary { op, left, right } => !Binary { op, left, right }.simplify(),
+        self.simplify_with(SimplifyRules::all())
+    }
+
+    pub fn simplify_with(self, rules: SimplifyRules) -> Box<Node> {
+        let constants = rules.contains(SimplifyRules::CONSTANTS);
+        let double_negation = rules.contains(SimplifyRules::DOUBLE_NEGATION);
+        let equal_operands = rules.contains(SimplifyRules::EQUAL_OPERANDS);
+        match self.into_parts() {
+            NodeParts::Const(val) => Box::new(Const(val)),
+            NodeParts::Var(v) => Box::new(Var(v)),
+            NodeParts::Not(n) => match n.into_parts() {
+                NodeParts::Const(val) if constants => Box::new(Const(!val)),
+                NodeParts::Not(n) if double_negation => n.simplify_with(rules),
+                n => !Node::from(n).simplify_with(rules),
             },
-            Binary { op, left, right } => {
-                let left = left.simplify();
-                let right = right.simplify();
+            NodeParts::Binary { op, left, right } => {
+                let left = left.simplify_with(rules);
+                let right = right.simplify_with(rules);
+                let binary = |left, right| Binary {
+                    op,
+                    left: Box::new(left),
+                    right: Box::new(right),
+                };
                 match op {
                     And => Box::new(match (*left, *right) {
-                        (Const(false), _) | (_, Const(false)) => Const(false),
-                        (Const(true), right) => right,
-                        (left, Const(true)) => left,
+                        (Const(false), _) | (_, Const(false)) if constants => Const(false),
+                        (Const(true), right) if constants => right,
+                        (left, Const(true)) if constants => left,
                         (left, right) => {
-                            if left.equals(&right) {
+                            if equal_operands && left.equals(&right) {
                                 left
                             } else {
-                                Binary {
-                                    op,
-                                    left: Box::new(left),
-                                    right: Box::new(right),
-                                }
+                                binary(left, right)
                             }
                         }
                     }),
                     Or => Box::new(match (*left, *right) {
-                        (Const(true), _) | (_, Const(true)) => Const(true),
-                        (Const(false), right) => right,
-                        (left, Const(false)) => left,
+                        (Const(true), _) | (_, Const(true)) if constants => Const(true),
+                        (Const(false), right) if constants => right,
+                        (left, Const(false)) if constants => left,
                         (left, right) => {
-                            if left.equals(&right) {
+                            if equal_operands && left.equals(&right) {
                                 left
                             } else {
-                                Binary {
-                                    op,
-                                    left: Box::new(left),
-                                    right: Box::new(right),
-                                }
+                                binary(left, right)
                             }
                         }
                     }),
                     Xor => Box::new(match (*left, *right) {
-                        (Const(a), Const(b)) => Const(a ^ b),
-                        (Const(false), right) => right,
-                        (left, Const(false)) => left,
-                        (Const(true), right) => *(!right),
-                        (left, Const(true)) => *(!left),
+                        (Const(a), Const(b)) if constants => Const(a ^ b),
+                        (Const(false), right) if constants => right,
+                        (left, Const(false)) if constants => left,
+                        (Const(true), right) if constants => *(!right),
+                        (left, Const(true)) if constants => *(!left),
                         (left, right) => {
-                            if left.equals(&right) {
+                            if equal_operands && left.equals(&right) {
                                 Const(false)
                             } else {
-                                Binary {
-                                    op,
-                                    left: Box::new(left),
-                                    right: Box::new(right),
-                                }
+                                binary(left, right)
                             }
                         }
                     }),
                     Leq => Box::new(match (*left, *right) {
-                        (Const(a), Const(b)) => Const(a == b),
-                        (Const(false), right) => *(!right),
-                        (left, Const(false)) => *(!left),
-                        (Const(true), right) => right,
-                        (left, Const(true)) => left,
+                        (Const(a), Const(b)) if constants => Const(a == b),
+                        (Const(false), right) if constants => *(!right),
+                        (left, Const(false)) if constants => *(!left),
+                        (Const(true), right) if constants => right,
+                        (left, Const(true)) if constants => left,
                         (left, right) => {
-                            if left.equals(&right) {
+                            if equal_operands && left.equals(&right) {
                                 Const(true)
                             } else {
-                                Binary {
-                                    op,
-                                    left: Box::new(left),
-                                    right: Box::new(right),
-                                }
+                                binary(left, right)
                             }
                         }
                     }),
                     Impl => Box::new(match (*left, *right) {
-                        (Const(false), _) | (_, Const(true)) => Const(true),
-                        (Const(true), right) => right,
-                        (left, Const(false)) => *(!left),
+                        (Const(false), _) | (_, Const(true)) if constants => Const(true),
+                        (Const(true), right) if constants => right,
+                        (left, Const(false)) if constants => *(!left),
                         (left, right) => {
-                            if left.equals(&right) {
+                            if equal_operands && left.equals(&right) {
                                 Const(true)
                             } else {
-                                Binary {
-                                    op,
-                                    left: Box::new(left),
-                                    right: Box::new(right),
+                                match right.into_parts() {
+                                    // A > (A > B) -> A > B
+                                    NodeParts::Binary {
+                                        op: Impl,
+                                        left: antecedent,
+                                        right: consequent,
+                                    } if equal_operands && antecedent.equals(&left) => {
+                                        binary(left, *consequent)
+                                    }
+                                    // A > (B > A) -> 1
+                                    NodeParts::Binary {
+                                        op: Impl,
+                                        right: consequent,
+                                        ..
+                                    } if equal_operands && consequent.equals(&left) => Const(true),
+                                    right => binary(left, Node::from(right)),
                                 }
                             }
                         }
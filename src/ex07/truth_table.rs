@@ -0,0 +1,81 @@
+// a function as the bits of its truth table, combined a word at a time
+
+use crate::node::{TableError, Tree};
+
+// bit i of the table is the result on row i, the first variable being the MSB of i
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub struct TruthTable {
+    vars: Vec<char>,
+    rows: usize,
+    // the bits past the last row are kept at 0, so == can compare words
+    words: Vec<u64>,
+}
+
+impl TruthTable {
+    pub fn from_formula(tree: &Tree) -> Result<TruthTable, TableError> {
+        let table = tree.truth_table()?;
+        let vars = tree.varlist().to_vec();
+        let rows: usize = 1 << vars.len();
+        let mut words = vec![0; rows.div_ceil(64)];
+        for (i, res) in table.into_iter().enumerate() {
+            words[i / 64] |= (res as u64) << (i % 64);
+        }
+        Ok(TruthTable { vars, rows, words })
+    }
+
+    pub fn vars(&self) -> &[char] {
+        &self.vars
+    }
+
+    pub fn get(&self, row: usize) -> bool {
+        (self.words[row / 64] >> (row % 64)) & 1 == 1
+    }
+
+    pub fn count_ones(&self) -> usize {
+        self.words.iter().map(|w| w.count_ones() as usize).sum()
+    }
+
+    fn zip_with(mut self, other: &TruthTable, f: impl Fn(u64, u64) -> u64) -> TruthTable {
+        assert_eq!(self.vars, other.vars, "tables over different variables");
+        for (word, other) in self.words.iter_mut().zip(&other.words) {
+            *word = f(*word, *other);
+        }
+        self
+    }
+}
+
+impl std::ops::BitAnd for TruthTable {
+    type Output = TruthTable;
+    fn bitand(self, other: TruthTable) -> TruthTable {
+        self.zip_with(&other, |a, b| a & b)
+    }
+}
+
+impl std::ops::BitOr for TruthTable {
+    type Output = TruthTable;
+    fn bitor(self, other: TruthTable) -> TruthTable {
+        self.zip_with(&other, |a, b| a | b)
+    }
+}
+
+impl std::ops::BitXor for TruthTable {
+    type Output = TruthTable;
+    fn bitxor(self, other: TruthTable) -> TruthTable {
+        self.zip_with(&other, |a, b| a ^ b)
+    }
+}
+
+impl std::ops::Not for TruthTable {
+    type Output = TruthTable;
+    fn not(mut self) -> TruthTable {
+        for word in &mut self.words {
+            *word = !*word;
+        }
+        // clear the bits past the last row again
+        if !self.rows.is_multiple_of(64) {
+            let last = self.words.len() - 1;
+            self.words[last] &= (1 << (self.rows % 64)) - 1;
+        }
+        self
+    }
+}
@@ -0,0 +1,54 @@
+// several formulas over one set of variables, evaluated together
+
+use crate::node::{new_variables, Node, NodeParts, ParseError, Tree, VarCell, Variable};
+use std::collections::HashMap;
+
+pub struct Workbook {
+    // one cell per letter, shared by every formula
+    variables: Vec<VarCell>,
+    formulas: Vec<Node>,
+}
+
+// the same formula, with its variables taken from `variables`
+fn share(node: Node, variables: &[VarCell]) -> Node {
+    match node.into_parts() {
+        NodeParts::Const(c) => Node::Const(c),
+        NodeParts::Var(v) => Node::Var(variables[v.get().name as usize - 'A' as usize].clone()),
+        NodeParts::Not(n) => Node::Not(Box::new(share(*n, variables))),
+        NodeParts::Binary { op, left, right } => Node::Binary {
+            op,
+            left: Box::new(share(*left, variables)),
+            right: Box::new(share(*right, variables)),
+        },
+    }
+}
+
+impl Workbook {
+    pub fn new() -> Workbook {
+        Workbook {
+            variables: new_variables(),
+            formulas: Vec::new(),
+        }
+    }
+
+    // adds a formula in rpn, returns its index in the results of eval_all
+    pub fn add(&mut self, formula: &str) -> Result<usize, ParseError> {
+        let tree = formula.parse::<Tree>()?;
+        self.formulas.push(share(tree.root, &self.variables));
+        Ok(self.formulas.len() - 1)
+    }
+
+    pub fn formulas(&self) -> &[Node] {
+        &self.formulas
+    }
+
+    // the result of every formula, variables missing from the assignment are false
+    pub fn eval_all(&self, assignment: &HashMap<char, bool>) -> Vec<bool> {
+        for cell in &self.variables {
+            let name = cell.get().name;
+            let value = assignment.get(&name).copied().unwrap_or(false);
+            cell.set(Variable { name, value });
+        }
+        self.formulas.iter().map(Node::eval).collect()
+    }
+}
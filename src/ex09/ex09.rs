@@ -1,10 +1,14 @@
 // an AST to parse logical expressions in rpn
 
+#[allow(dead_code)]
+#[path = "../arg_error.rs"]
+mod arg_error;
 mod dot_graph;
 mod expr_generator;
 mod node;
 
 use crate::node::Tree;
+use arg_error::ArgError;
 use dot_graph::create_graph;
 use expr_generator::random_rpn_expr;
 use node::ParseError;
@@ -13,12 +17,16 @@ use std::env::args;
 struct Args {
     expr: String,
     sets: Vec<Vec<i32>>,
+    universe: Option<Vec<i32>>,
     dot: bool,
 }
 
-fn eval_set(formula: &str, sets: &[Vec<i32>]) -> Vec<i32> {
+fn eval_set(formula: &str, sets: &[Vec<i32>], universe: Option<&[i32]>) -> Vec<i32> {
     match formula.parse::<Tree>() {
-        Ok(tree) => tree.eval_set(sets),
+        Ok(tree) => match universe {
+            Some(universe) => tree.eval_set_in(sets, universe),
+            None => tree.eval_set(sets),
+        },
         Err(e) => {
             eprintln!("{:?}", e);
             vec![]
@@ -26,55 +34,73 @@ fn eval_set(formula: &str, sets: &[Vec<i32>]) -> Vec<i32> {
     }
 }
 
-fn parse_args() -> Result<Args, String> {
-    let mut args = args();
+fn parse_set(arg: String) -> Result<Vec<i32>, ArgError> {
+    let set: Result<Vec<i32>, _> = arg.split(',').map(str::parse).collect();
+    set.map_err(|_| ArgError::InvalidSet(arg))
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Args, ArgError> {
     let mut expr = String::new();
     let mut sets = Vec::new();
+    let mut universe = None;
     let mut dot = false;
-    let path = args.next().unwrap_or_else(|| "ex09".to_string());
-
-    for arg in args {
-        if let Some(arg) = arg.strip_prefix('-') {
+    while let Some(arg) = args.next() {
+        if arg == "--universe" {
+            // sorted and deduped like the default universe
+            let mut set = parse_set(args.next().unwrap_or_default())?;
+            set.sort_unstable();
+            set.dedup();
+            universe = Some(set);
+        } else if let Some(arg) = arg.strip_prefix('-') {
             for c in arg.chars() {
                 match c {
                     'd' => dot = true,
                     'r' => {
-                        if expr.is_empty() {
-                            expr = random_rpn_expr(3, 5);
-                        } else {
-                            return Err(path);
+                        if !expr.is_empty() {
+                            return Err(ArgError::ConflictingRandom);
                         }
+                        expr = random_rpn_expr(3, 5);
                     }
-                    _ => return Err(path),
+                    _ => return Err(ArgError::UnknownFlag(c)),
                 }
             }
         } else if expr.is_empty() {
             expr = arg;
         } else {
-            let set: Result<Vec<i32>, _> = arg.split(',').map(str::parse).collect();
-            match set {
-                Ok(set) => sets.push(set),
-                Err(_) => return Err(path),
-            }
+            sets.push(parse_set(arg)?);
         }
     }
     if expr.is_empty() {
-        Err(path)
+        Err(ArgError::MissingFormula)
     } else {
-        Ok(Args { expr, sets, dot })
+        Ok(Args {
+            expr,
+            sets,
+            universe,
+            dot,
+        })
     }
 }
 
 fn main() -> Result<(), ParseError> {
-    let (expr, sets, dot) = match parse_args() {
-        Ok(args) => (args.expr, args.sets, args.dot),
-        Err(path) => {
-            println!("Usage: {} <formula sets | -r> [-d]", path);
+    let mut args = args();
+    let path = args.next().unwrap_or_else(|| "ex09".to_string());
+    let (expr, sets, universe, dot) = match parse_args(args) {
+        Ok(args) => (args.expr, args.sets, args.universe, args.dot),
+        Err(e) => {
+            println!("Error: {:?}", e);
+            println!(
+                "Usage: {} <formula sets | -r> [-d] [--universe <set>]",
+                path
+            );
             println!("formula: a propositional boolean formula in rpn, ex: AB&C|");
             println!("sets: a list of sets of integers, ex: 1,2,3 4,5,6");
             println!("Options:");
             println!("  -r  use a randomly generated formula");
             println!("  -d  print the dot graph of the formula and generate an image from it");
+            println!(
+                "  --universe  the set complements are taken in, the union of the sets by default"
+            );
             return Ok(());
         }
     };
@@ -83,9 +109,55 @@ fn main() -> Result<(), ParseError> {
         create_graph(&expr.parse::<Tree>()?.root, "ex09_in");
     }
     println!("Sets:\n{:?}", sets);
-    println!("{:?}", eval_set(&expr, &sets));
+    if let Some(universe) = &universe {
+        println!("Universe:\n{:?}", universe);
+    }
+    println!("{:?}", eval_set(&expr, &sets, universe.as_deref()));
     Ok(())
 }
 
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+
+    fn simplify(formula: &str) -> String {
+        formula
+            .parse::<Tree>()
+            .expect("input is valid")
+            .root
+            .simplify()
+            .to_string()
+    }
+
+    #[test]
+    fn ex09_simplify_complementary() {
+        assert_eq!(simplify("AA!="), "0");
+        assert_eq!(simplify("A!A="), "0");
+        assert_eq!(simplify("AA!^"), "1");
+        assert_eq!(simplify("A!A^"), "1");
+        assert_eq!(simplify("AA!>"), "A!");
+        assert_eq!(simplify("A!A>"), "A");
+        assert_eq!(simplify("AB&AB&!="), "0");
+        assert_eq!(simplify("AB&BA&!^"), "1");
+    }
+
+    #[test]
+    fn ex09_parse_args() {
+        let parse = |args: &[&str]| parse_args(args.iter().map(|s| s.to_string())).err();
+        arg_error::check_common_flags(parse);
+        assert_eq!(parse(&["AB&", "1,2", "3,4"]), None);
+        assert_eq!(
+            parse(&["AB&", "1,2", "3,x"]),
+            Some(ArgError::InvalidSet("3,x".to_string()))
+        );
+        assert_eq!(parse(&["A!", "1,2", "--universe", "1,2,3"]), None);
+        assert_eq!(
+            parse(&["A!", "--universe", "1,y"]),
+            Some(ArgError::InvalidSet("1,y".to_string()))
+        );
+        assert_eq!(
+            parse(&["A!", "--universe"]),
+            Some(ArgError::InvalidSet(String::new()))
+        );
+    }
+}
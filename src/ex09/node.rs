@@ -307,15 +307,21 @@ impl Tree {
             .value = vec;
     }
 
+    // the universe being the union of the sets
     pub fn eval_set(&self, sets: &[Vec<i32>]) -> Vec<i32> {
         let mut universe = sets.iter().flatten().copied().collect::<Vec<_>>();
         universe.sort_unstable();
         universe.dedup();
+        self.eval_set_in(sets, &universe)
+    }
+
+    // complements are taken in `universe`, and the result keeps only what belongs to it
+    pub fn eval_set_in(&self, sets: &[Vec<i32>], universe: &[i32]) -> Vec<i32> {
         for (i, var) in self.varlist.iter().enumerate() {
             self.set_vec(*var, sets.get(i).unwrap_or(&vec![]).clone());
         }
         match self.root.eval_set() {
-            Positive(a) => a,
+            Positive(a) => a.into_iter().filter(|val| universe.contains(val)).collect(),
             Negative(a) => universe
                 .iter()
                 .filter(|&val| !a.contains(val))
@@ -429,6 +435,13 @@ impl Node {
         }
     }
 
+    fn is_negation_of(&self, other: &Node) -> bool {
+        match (self, other) {
+            (Not(a), b) | (b, Not(a)) => a.equals(b),
+            _ => false,
+        }
+    }
+
     pub fn simplify(self) -> Box<Node> {
         match self {
             Const(val) => Box::new(Const(val)),
@@ -484,6 +497,8 @@ impl Node {
                         (left, right) => {
                             if left.equals(&right) {
                                 Const(false)
+                            } else if left.is_negation_of(&right) {
+                                Const(true)
                             } else {
                                 Binary {
                                     op,
@@ -502,6 +517,8 @@ impl Node {
                         (left, right) => {
                             if left.equals(&right) {
                                 Const(true)
+                            } else if left.is_negation_of(&right) {
+                                Const(false)
                             } else {
                                 Binary {
                                     op,
@@ -518,6 +535,9 @@ impl Node {
                         (left, right) => {
                             if left.equals(&right) {
                                 Const(true)
+                            } else if left.is_negation_of(&right) {
+                                // A > !A -> !A, !A > A -> A
+                                right
                             } else {
                                 Binary {
                                     op,
@@ -0,0 +1,12 @@
+use std::process::Command;
+
+#[test]
+fn ex04_cli_csv() {
+    let output = Command::new(env!("CARGO_BIN_EXE_ex04"))
+        .args(["AB&", "--format", "csv"])
+        .output()
+        .expect("ex04 runs");
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).expect("output is utf-8");
+    assert_eq!(stdout, "Input:\nAB&\nA,B,=\n0,0,0\n0,1,0\n1,0,0\n1,1,1\n");
+}
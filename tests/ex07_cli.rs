@@ -0,0 +1,72 @@
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Command, Stdio};
+
+fn run(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_ex07"))
+        .args(args)
+        .output()
+        .expect("ex07 runs");
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).expect("output is utf-8")
+}
+
+#[test]
+fn ex07_cli_count() {
+    assert_eq!(run(&["AB|", "-c"]).lines().last(), Some("3"));
+    assert_eq!(run(&["AA!&", "-c"]).lines().last(), Some("0"));
+    assert_eq!(run(&["AB|"]).lines().last(), Some("true"));
+}
+
+fn run_stdin(args: &[&str], input: &[u8]) -> String {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ex07"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("ex07 runs");
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    stdin.write_all(input).expect("stdin is open");
+    drop(stdin);
+    let output = child.wait_with_output().expect("ex07 exits");
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).expect("output is utf-8")
+}
+
+// one answer per line, a bad line does not stop the run
+#[test]
+fn ex07_cli_stdin() {
+    let input = b"AB|\nA&\n\nAA!&\n";
+    assert_eq!(
+        run_stdin(&["-"], input),
+        "true\nError: Missing operand\nfalse\n"
+    );
+    assert_eq!(
+        run_stdin(&["-", "-c"], input),
+        "3\nError: Missing operand\n0\n"
+    );
+    // the lines before one that isn't utf-8 are still answered
+    assert_eq!(run_stdin(&["-"], b"AB&\n\xff\nAB|\n"), "true\n");
+}
+
+// each line is answered before the next one is read, not at the end of the input
+#[test]
+fn ex07_cli_stdin_streams() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_ex07"))
+        .arg("-")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .expect("ex07 runs");
+    let mut stdin = child.stdin.take().expect("stdin is piped");
+    let mut stdout = BufReader::new(child.stdout.take().expect("stdout is piped"));
+    let mut line = String::new();
+    for (input, answer) in [("AB|\n", "true\n"), ("AA!&\n", "false\n")] {
+        stdin.write_all(input.as_bytes()).expect("stdin is open");
+        stdin.flush().expect("stdin is open");
+        line.clear();
+        stdout.read_line(&mut line).expect("ex07 answers");
+        assert_eq!(line, answer);
+    }
+    drop(stdin);
+    assert!(child.wait().expect("ex07 exits").success());
+}
@@ -0,0 +1,30 @@
+use std::process::Command;
+
+fn run(args: &[&str]) -> String {
+    let output = Command::new(env!("CARGO_BIN_EXE_ex09"))
+        .args(args)
+        .output()
+        .expect("ex09 runs");
+    assert!(output.status.success());
+    String::from_utf8(output.stdout).expect("output is utf-8")
+}
+
+#[test]
+fn ex09_cli_universe() {
+    let last = |args: &[&str]| run(args).lines().last().map(str::to_string);
+    assert_eq!(
+        last(&["A!", "1,2", "--universe", "1,2,3,4"]).as_deref(),
+        Some("[3, 4]")
+    );
+    // without it, the complement is taken in the union of the sets
+    assert_eq!(last(&["A!", "1,2", "3"]).as_deref(), Some("[3]"));
+    assert_eq!(
+        last(&["AB|!", "1", "2", "--universe", "1,2,3"]).as_deref(),
+        Some("[3]")
+    );
+    // an explicit universe is sorted and deduped too
+    assert_eq!(
+        last(&["A!", "1", "--universe", "3,1,3,2"]).as_deref(),
+        Some("[2, 3]")
+    );
+}